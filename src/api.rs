@@ -1,15 +1,59 @@
 use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
 
-use log::debug;
-use reqwest::{Client, RequestBuilder};
+use clap::ValueEnum;
+use log::{debug, warn};
+use reqwest::header::{AUTHORIZATION, CONTENT_ENCODING, CONTENT_TYPE, HeaderMap};
+use reqwest::{
+    Certificate, Client, ClientBuilder, Request, RequestBuilder, Response, StatusCode, Url,
+    redirect,
+};
 
 use crate::error::{Error, Result};
+use crate::netrc;
+use crate::signing::Signer;
+use crate::warnings;
 
+/// Read an environment variable, treating empty or whitespace-only values as missing.
 fn required_env(name: &'static str) -> Result<String> {
-    env::var(name).map_err(|e| match e {
+    let value = env::var(name).map_err(|e| match e {
         env::VarError::NotPresent => Error::MissingEnvVar(name),
         _ => Error::InvalidEnvVar { name, source: e },
-    })
+    })?;
+    if value.trim().is_empty() {
+        return Err(Error::MissingEnvVar(name));
+    }
+    Ok(value)
+}
+
+/// Fallbacks for `ANTITHESIS_TENANT` and `ANTITHESIS_BASE_URL`, such as a
+/// config file profile. The environment variables win when they're set.
+#[derive(Debug, Clone, Default)]
+pub struct EnvDefaults {
+    pub tenant: Option<String>,
+    pub base_url: Option<String>,
+}
+
+impl EnvDefaults {
+    fn tenant(&self) -> Result<String> {
+        match required_env("ANTITHESIS_TENANT") {
+            Err(Error::MissingEnvVar(name)) => {
+                self.tenant.clone().ok_or(Error::MissingEnvVar(name))
+            }
+            result => result,
+        }
+    }
+
+    fn base_url(&self) -> Option<String> {
+        env::var("ANTITHESIS_BASE_URL")
+            .ok()
+            .or_else(|| self.base_url.clone())
+    }
 }
 
 #[derive(Clone)]
@@ -17,67 +61,484 @@ pub struct Config {
     pub username: String,
     pub password: String,
     pub tenant: String,
+    /// Overrides the `https://<tenant>.antithesis.com/api/v1` base URL.
+    pub base_url: Option<String>,
+    pub timeouts: Timeouts,
+    /// How many redirects to follow; 0 returns the redirect response itself.
+    pub max_redirects: usize,
 }
 
+/// reqwest's default redirect limit.
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
 impl Config {
     pub fn new(username: String, password: String, tenant: String) -> Self {
         Self {
             username,
             password,
             tenant,
+            base_url: None,
+            timeouts: Timeouts::default(),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
         }
     }
 
+    /// Load config from the environment.
+    ///
+    /// If neither `ANTITHESIS_USERNAME` nor `ANTITHESIS_PASSWORD` is set, the
+    /// credentials are looked up in `.netrc` for the API host.
     pub fn from_env() -> Result<Self> {
+        Self::from_env_with(&EnvDefaults::default())
+    }
+
+    /// Load config from the environment, using `defaults` for the tenant and
+    /// base URL when their variables are unset.
+    pub fn from_env_with(defaults: &EnvDefaults) -> Result<Self> {
         debug!("loading config from environment");
-        Ok(Self {
-            username: required_env("ANTITHESIS_USERNAME")?,
-            password: required_env("ANTITHESIS_PASSWORD")?,
-            tenant: required_env("ANTITHESIS_TENANT")?,
-        })
+        let username = required_env("ANTITHESIS_USERNAME");
+        let password = required_env("ANTITHESIS_PASSWORD");
+        let netrc = match (&username, &password) {
+            (Err(Error::MissingEnvVar(_)), Err(Error::MissingEnvVar(_))) => {
+                api_host(defaults).and_then(|host| netrc::credentials(&host))
+            }
+            _ => None,
+        };
+        let (username, password) = match netrc {
+            Some(credentials) => credentials,
+            None => (username?, password?),
+        };
+
+        let config = Self {
+            username,
+            password,
+            tenant: defaults.tenant()?,
+            base_url: defaults.base_url(),
+            timeouts: Timeouts::default(),
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+        };
+        for warning in config.warnings() {
+            warnings::warn(warning)?;
+        }
+        Ok(config)
+    }
+
+    /// Heuristic warnings for credentials that look like they were put in the
+    /// wrong variables, such as the tenant in the username.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.username == self.tenant || self.username.contains(".antithesis.com") {
+            warnings.push(format!(
+                "ANTITHESIS_USERNAME {:?} looks like a tenant; check that the credentials aren't swapped",
+                self.username
+            ));
+        }
+        if self.password == self.username || self.password == self.tenant {
+            warnings.push(
+                "ANTITHESIS_PASSWORD is the same as the username or tenant; check that the credentials aren't swapped"
+                    .to_string(),
+            );
+        }
+        if self.tenant.contains('.') {
+            warnings.push(format!(
+                "ANTITHESIS_TENANT {:?} should be just the tenant name (e.g. acme, not acme.antithesis.com)",
+                self.tenant
+            ));
+        }
+        warnings
+    }
+}
+
+/// Fail if `body` is larger than `ANTITHESIS_MAX_BODY_BYTES`, when that is set.
+///
+/// This catches runaway params (e.g. a huge file piped to `--stdin`) before
+/// they're sent.
+pub fn check_body_size(body: &str) -> Result<()> {
+    let Ok(limit) = env::var("ANTITHESIS_MAX_BODY_BYTES") else {
+        return Ok(());
+    };
+    let limit: usize = limit.trim().parse().map_err(|_| {
+        Error::InvalidArgs(format!(
+            "ANTITHESIS_MAX_BODY_BYTES must be a number of bytes, got {:?}",
+            limit
+        ))
+    })?;
+    if body.len() > limit {
+        return Err(Error::InvalidArgs(format!(
+            "request body is {} bytes, over the ANTITHESIS_MAX_BODY_BYTES limit of {}",
+            body.len(),
+            limit
+        )));
+    }
+    Ok(())
+}
+
+/// The base URL for `tenant` when no override is set.
+fn default_base_url(tenant: &str) -> String {
+    format!("https://{}.antithesis.com/api/v1", tenant)
+}
+
+/// The host API requests go to, from the base URL or the tenant.
+fn api_host(defaults: &EnvDefaults) -> Option<String> {
+    let base_url = match defaults.base_url() {
+        Some(base_url) => base_url,
+        None => default_base_url(&defaults.tenant().ok()?),
+    };
+    Url::parse(&base_url).ok()?.host_str().map(str::to_string)
+}
+
+/// The connection settings [`Config::from_env_with`] would resolve, as
+/// `(name, value, source)` rows for `--print-config`. Credentials are masked.
+///
+/// Unlike loading the config, nothing here is required, so a missing setting
+/// shows as `(not set)` rather than failing. `profile` names the profile
+/// `defaults` came from.
+pub fn describe_env(
+    defaults: &EnvDefaults,
+    profile: Option<&str>,
+) -> Vec<(&'static str, String, String)> {
+    let from_profile = || format!("profile {}", profile.unwrap_or_default());
+    let not_set = || ("(not set)".to_string(), "none".to_string());
+
+    let username = required_env("ANTITHESIS_USERNAME").ok();
+    let password = required_env("ANTITHESIS_PASSWORD").ok();
+    let (username, password) = match (username, password) {
+        (None, None) => match api_host(defaults).and_then(|host| netrc::credentials(&host)) {
+            Some((username, _)) => (
+                (mask(&username), "netrc".to_string()),
+                ("[REDACTED]".to_string(), "netrc".to_string()),
+            ),
+            None => (not_set(), not_set()),
+        },
+        (username, password) => (
+            username.map_or_else(not_set, |username| {
+                (mask(&username), "env ANTITHESIS_USERNAME".to_string())
+            }),
+            password.map_or_else(not_set, |_| {
+                (
+                    "[REDACTED]".to_string(),
+                    "env ANTITHESIS_PASSWORD".to_string(),
+                )
+            }),
+        ),
+    };
+
+    // The values are resolved as Config would; only the sources are worked
+    // out here
+    let tenant = match defaults.tenant() {
+        Ok(tenant) if required_env("ANTITHESIS_TENANT").is_ok() => {
+            (tenant, "env ANTITHESIS_TENANT".to_string())
+        }
+        Ok(tenant) => (tenant, from_profile()),
+        Err(_) => not_set(),
+    };
+    let base_url = match (defaults.base_url(), defaults.tenant()) {
+        (Some(base_url), _) if env::var("ANTITHESIS_BASE_URL").is_ok() => {
+            (base_url, "env ANTITHESIS_BASE_URL".to_string())
+        }
+        (Some(base_url), _) => (base_url, from_profile()),
+        (None, Ok(tenant)) => (default_base_url(&tenant), "default".to_string()),
+        (None, Err(_)) => not_set(),
+    };
+
+    vec![
+        ("username", username.0, username.1),
+        ("password", password.0, password.1),
+        ("tenant", tenant.0, tenant.1),
+        ("base_url", base_url.0, base_url.1),
+    ]
+}
+
+/// Show only the first two characters of `secret`.
+fn mask(secret: &str) -> String {
+    let shown: String = secret.chars().take(2).collect();
+    format!("{}***", shown)
+}
+
+/// How failed requests are retried.
+///
+/// Connection errors, 5xx responses, and any `retry_on` statuses are retried
+/// with exponential backoff, starting at `base_delay` and doubling after each
+/// attempt up to `max_delay`, with `jitter` applied.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    pub jitter: Jitter,
+    /// Statuses to retry in addition to 5xx, e.g. 409 during a rolling deploy.
+    pub retry_on: Vec<StatusCode>,
+    /// Retries shared with other requests; each retry spends one, on top of
+    /// the per-request `max_retries` limit.
+    pub budget: Option<RetryBudget>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: Jitter::None,
+            retry_on: Vec::new(),
+            budget: None,
+        }
     }
 }
 
+/// A pool of retries shared by several requests, so a flaky endpoint can't
+/// make every request in a batch use all of its retries.
+///
+/// Clones share the same pool.
+#[derive(Debug, Clone)]
+pub struct RetryBudget {
+    remaining: Arc<AtomicU32>,
+}
+
+impl RetryBudget {
+    pub fn new(retries: u32) -> Self {
+        Self {
+            remaining: Arc::new(AtomicU32::new(retries)),
+        }
+    }
+
+    /// Spend one retry, returning false if none are left.
+    fn take(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1))
+            .is_ok()
+    }
+}
+
+/// How much randomness to add to retry delays, so clients that failed
+/// together don't all retry at the same moment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Jitter {
+    /// Exactly the exponential delay, for deterministic backoff.
+    #[default]
+    None,
+    /// A random delay between zero and the exponential delay.
+    Full,
+    /// Half the exponential delay plus a random part of the other half.
+    Equal,
+}
+
+/// A small seeded PRNG (SplitMix64) for jitter, which only needs to spread
+/// retries out, not be unpredictable.
+struct JitterRng(u64);
+
+impl JitterRng {
+    fn from_entropy() -> Self {
+        Self(uuid::Uuid::new_v4().as_u64_pair().0)
+    }
+
+    /// A uniformly distributed number in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl RetryPolicy {
+    fn delay(&self, attempt: u32) -> Duration {
+        self.delay_with(attempt, &mut JitterRng::from_entropy())
+    }
+
+    fn delay_with(&self, attempt: u32, rng: &mut JitterRng) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt - 1))
+            .min(self.max_delay);
+        match self.jitter {
+            Jitter::None => backoff,
+            Jitter::Full => backoff.mul_f64(rng.next_f64()),
+            Jitter::Equal => backoff / 2 + (backoff / 2).mul_f64(rng.next_f64()),
+        }
+    }
+}
+
+/// Request timeouts for the API client.
+///
+/// The connect timeout is separate so a slow DNS lookup or connect fails fast
+/// without shortening the time allowed for the whole request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub total: Duration,
+    pub connect: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self {
+            total: Duration::from_secs(30),
+            connect: Duration::from_secs(10),
+        }
+    }
+}
+
+impl Timeouts {
+    fn client_builder(&self) -> ClientBuilder {
+        Client::builder()
+            .timeout(self.total)
+            .connect_timeout(self.connect)
+    }
+}
+
+/// Client for the Antithesis API.
+///
+/// Cloning is cheap: clones share the underlying `reqwest::Client` and its
+/// connection pool, which is reference counted internally. The client is
+/// `Send + Sync`, so a single instance can be cloned into many tasks instead
+/// of re-reading the environment and rebuilding it per request. Credentials
+/// are attached to each request individually.
+#[derive(Clone)]
 pub struct AntithesisApi {
     client: Client,
     base_url: String,
-    username: String,
-    password: String,
+    /// Username and password, or `None` for an unauthenticated local endpoint
+    credentials: Option<(String, String)>,
+    tenant: Option<String>,
+    /// Signs JSON bodies sent with [`post_json`](Self::post_json)
+    signer: Option<Signer>,
 }
 
 impl AntithesisApi {
     pub fn new(config: Config) -> Result<Self> {
-        let base_url = format!("https://{}.antithesis.com/api/v1", config.tenant);
+        let base_url = default_base_url(&config.tenant);
         debug!("using default base URL: {}", base_url);
         Self::with_base_url(config, base_url)
     }
 
     pub fn from_env() -> Result<Self> {
-        let config = Config::from_env()?;
-        // Allow base URL override for testing
-        if let Ok(base_url) = env::var("ANTITHESIS_BASE_URL") {
-            debug!("using ANTITHESIS_BASE_URL override: {}", base_url);
-            Self::with_base_url(config, base_url)
-        } else {
-            Self::new(config)
+        Self::from_config(Config::from_env()?)
+    }
+
+    /// Create a client for `config`, honoring its base URL override.
+    pub fn from_config(config: Config) -> Result<Self> {
+        match config.base_url.clone() {
+            Some(base_url) => {
+                debug!("using base URL override: {}", base_url);
+                Self::with_base_url(config, base_url)
+            }
+            None => Self::new(config),
         }
     }
 
     pub fn with_base_url(config: Config, base_url: impl Into<String>) -> Result<Self> {
-        let base_url = base_url.into().trim_end_matches('/').to_string();
+        let mut api = Self::build(
+            base_url.into(),
+            config.timeouts,
+            config.max_redirects,
+            Some((config.username, config.password)),
+        )?;
+        api.tenant = Some(config.tenant);
+        Ok(api)
+    }
+
+    /// Create a client that sends no credentials, for a locally running
+    /// endpoint such as a test-composer simulation.
+    pub fn unauthenticated(
+        base_url: impl Into<String>,
+        timeouts: Timeouts,
+        max_redirects: usize,
+    ) -> Result<Self> {
+        Self::build(base_url.into(), timeouts, max_redirects, None)
+    }
+
+    fn build(
+        base_url: String,
+        timeouts: Timeouts,
+        max_redirects: usize,
+        credentials: Option<(String, String)>,
+    ) -> Result<Self> {
+        let base_url = base_url.trim_end_matches('/').to_string();
+        validate_base_url(&base_url)?;
         debug!("initializing API client for {}", base_url);
-        let client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()?;
+        // reqwest drops the Authorization header when a redirect leads to
+        // another host or port, so credentials aren't leaked by a redirect
+        let redirects = match max_redirects {
+            0 => redirect::Policy::none(),
+            max => redirect::Policy::limited(max),
+        };
+        let mut builder = timeouts.client_builder().redirect(redirects);
+        if let Some(path) = env::var_os("ANTITHESIS_CA_BUNDLE").filter(|p| !p.is_empty()) {
+            builder = add_root_certificates(builder, load_ca_bundle(Path::new(&path))?);
+        }
+        let client = builder.build()?;
 
         Ok(Self {
             client,
             base_url,
-            username: config.username,
-            password: config.password,
+            credentials,
+            tenant: None,
+            signer: None,
         })
     }
 
+    /// Sign the body of every [`post_json`](Self::post_json) request.
+    pub fn with_signer(mut self, signer: Option<Signer>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Send a request, retrying according to `policy`.
+    ///
+    /// Returns the final response along with the number of attempts it took.
+    pub async fn send_with_retry(
+        &self,
+        request: RequestBuilder,
+        policy: &RetryPolicy,
+    ) -> Result<(Response, u32)> {
+        let mut attempt = 1;
+        loop {
+            let this_try = request
+                .try_clone()
+                .expect("request bodies are buffered, not streamed");
+            let result = this_try.send().await;
+            let retryable = match &result {
+                Ok(response) => {
+                    response.status().is_server_error()
+                        || policy.retry_on.contains(&response.status())
+                }
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !retryable || attempt > policy.max_retries {
+                return Ok((result?, attempt));
+            }
+            if let Some(budget) = &policy.budget
+                && !budget.take()
+            {
+                warn!("retry budget exhausted after attempt {}", attempt);
+                return Ok((result?, attempt));
+            }
+
+            let delay = policy.delay(attempt);
+            match &result {
+                Ok(response) => warn!("attempt {} got {}", attempt, response.status()),
+                Err(e) => warn!("attempt {} failed: {}", attempt, e),
+            }
+            debug!("retrying in {:?}", delay);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Attach the credentials, if this client has any.
+    fn authed(&self, request: RequestBuilder) -> RequestBuilder {
+        match &self.credentials {
+            Some((username, password)) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+
+    /// The tenant this client was configured for, if any.
+    pub fn tenant(&self) -> Option<&str> {
+        self.tenant.as_deref()
+    }
+
     pub fn base_url(&self) -> &str {
         &self.base_url
     }
@@ -85,24 +546,245 @@ impl AntithesisApi {
     pub fn get(&self, path: &str) -> RequestBuilder {
         let url = format!("{}{}", self.base_url, path);
         debug!("GET {}", url);
-        self.client
-            .get(url)
-            .basic_auth(&self.username, Some(&self.password))
+        self.authed(self.client.get(url))
+    }
+
+    /// GET an absolute URL, with this client's credentials only if the URL
+    /// has the same origin (scheme, host, and port) as the base URL, so they
+    /// aren't sent to another host or over plain HTTP.
+    pub fn get_url(&self, url: &str) -> RequestBuilder {
+        debug!("GET {}", url);
+        let request = self.client.get(url);
+        if self.is_same_origin(url) {
+            self.authed(request)
+        } else {
+            debug!(
+                "not sending credentials to {}, outside the API base URL",
+                url
+            );
+            request
+        }
+    }
+
+    fn is_same_origin(&self, url: &str) -> bool {
+        match (Url::parse(url), Url::parse(&self.base_url)) {
+            (Ok(url), Ok(base_url)) => url.origin() == base_url.origin(),
+            _ => false,
+        }
+    }
+
+    /// Render a `curl` command equivalent to POSTing the JSON `body` to `path`.
+    ///
+    /// Credentials are never included; the command reads them from the
+    /// `ANTITHESIS_USERNAME` and `ANTITHESIS_PASSWORD` environment variables,
+    /// unless this client is unauthenticated.
+    pub fn curl_command(&self, path: &str, body: &str) -> String {
+        let auth = match self.credentials {
+            Some(_) => "  -u \"$ANTITHESIS_USERNAME:$ANTITHESIS_PASSWORD\" \\\n",
+            None => "",
+        };
+        let signature = match &self.signer {
+            Some(signer) => format!(
+                "  -H {} \\\n",
+                shell_quote(&format!(
+                    "{}: {}",
+                    signer.header(),
+                    signer.signature(body.as_bytes())
+                ))
+            ),
+            None => String::new(),
+        };
+        format!(
+            "curl -X POST {} \\\n{}  -H 'Content-Type: application/json' \\\n{}  -d {}",
+            shell_quote(&format!("{}{}", self.base_url, path)),
+            auth,
+            signature,
+            shell_quote(body)
+        )
     }
 
     pub fn post(&self, path: &str) -> RequestBuilder {
         let url = format!("{}{}", self.base_url, path);
         debug!("POST {}", url);
-        self.client
-            .post(url)
-            .basic_auth(&self.username, Some(&self.password))
+        self.authed(self.client.post(url))
     }
+
+    /// POST a JSON `body` to `path`, signed if this client has a signer.
+    /// POST a launch body as JSON with a fresh `Idempotency-Key`.
+    ///
+    /// Retries send the same key, so a launch that reached the server before a
+    /// timeout or 5xx isn't started twice by a server that honors it.
+    pub fn post_launch(&self, path: &str, body: String) -> RequestBuilder {
+        self.post_json(path, body)
+            .header("Idempotency-Key", uuid::Uuid::new_v4().to_string())
+    }
+
+    pub fn post_json(&self, path: &str, body: String) -> RequestBuilder {
+        let request = self.post(path).header(CONTENT_TYPE, "application/json");
+        let request = match &self.signer {
+            Some(signer) => request.header(signer.header(), signer.signature(body.as_bytes())),
+            None => request,
+        };
+        request.body(body)
+    }
+}
+
+/// Read the root certificates in a PEM file, such as an internal CA named by
+/// `ANTITHESIS_CA_BUNDLE`. A file with no certificates is an error.
+pub fn load_ca_bundle(path: &Path) -> Result<Vec<Certificate>> {
+    let invalid = |message: String| Error::CaBundle {
+        path: path.to_path_buf(),
+        message,
+    };
+    let pem = fs::read(path).map_err(|e| invalid(e.to_string()))?;
+    let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| invalid(e.to_string()))?;
+    if certificates.is_empty() {
+        return Err(invalid("no PEM certificates found".to_string()));
+    }
+    debug!(
+        "trusting {} extra root certificates from {}",
+        certificates.len(),
+        path.display()
+    );
+    Ok(certificates)
+}
+
+/// Trust `certificates` in addition to the system roots.
+fn add_root_certificates(builder: ClientBuilder, certificates: Vec<Certificate>) -> ClientBuilder {
+    certificates
+        .into_iter()
+        .fold(builder, ClientBuilder::add_root_certificate)
+}
+
+/// Require an absolute http(s) URL, so mistakes like `localhost:8080` fail early
+/// with a clear message instead of deep inside reqwest.
+fn validate_base_url(base_url: &str) -> Result<()> {
+    let invalid =
+        |reason: &str| Error::InvalidArgs(format!("invalid base URL {:?}: {}", base_url, reason));
+    let url = Url::parse(base_url).map_err(|e| invalid(&e.to_string()))?;
+    if !matches!(url.scheme(), "http" | "https") || !url.has_host() {
+        return Err(invalid("expected an absolute http:// or https:// URL"));
+    }
+    Ok(())
+}
+
+/// A `key=value` query parameter appended to a launch request URL.
+#[derive(Debug, Clone)]
+pub struct QueryParam {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for QueryParam {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok(Self {
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+            _ => Err(format!("expected KEY=VALUE, got {:?}", s)),
+        }
+    }
+}
+
+/// Append URL-encoded query parameters to a request path.
+pub fn with_query(path: &str, query: &[QueryParam]) -> String {
+    if query.is_empty() {
+        return path.to_string();
+    }
+    let encoded = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(query.iter().map(|q| (&q.key, &q.value)))
+        .finish();
+    format!("{}?{}", path, encoded)
+}
+
+/// Fail if a response still carries a `Content-Encoding`.
+///
+/// The client transparently decodes gzip and deflate (and strips the header
+/// when it does), so anything left over is an encoding it can't read.
+pub fn check_content_encoding(response: &Response) -> Result<()> {
+    match response.headers().get(CONTENT_ENCODING) {
+        Some(encoding) if encoding != "identity" => Err(Error::UnexpectedResponse(format!(
+            "server sent a body with unsupported Content-Encoding {:?}",
+            String::from_utf8_lossy(encoding.as_bytes())
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Render a request for `--trace-request`: the request line, headers, and
+/// body, with the `Authorization` header redacted.
+pub fn trace_request(request: &Request) -> String {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .map(|bytes| String::from_utf8_lossy(bytes).into_owned())
+        .unwrap_or_default();
+    format!(
+        "> {} {}\n{}>\n{}",
+        request.method(),
+        request.url(),
+        trace_headers("> ", request.headers()),
+        prefix_lines("> ", &body)
+    )
+}
+
+/// Render a response for `--trace-request`: the status, headers, and body.
+pub fn trace_response(status: StatusCode, headers: &HeaderMap, body: &str) -> String {
+    format!(
+        "< {}\n{}<\n{}",
+        status,
+        trace_headers("< ", headers),
+        prefix_lines("< ", body)
+    )
+}
+
+fn trace_headers(prefix: &str, headers: &HeaderMap) -> String {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if name == AUTHORIZATION {
+                "[REDACTED]".into()
+            } else {
+                String::from_utf8_lossy(value.as_bytes())
+            };
+            format!("{}{}: {}\n", prefix, title_case(name.as_str()), value)
+        })
+        .collect()
+}
+
+/// `content-type` -> `Content-Type`
+fn title_case(name: &str) -> String {
+    name.split('-')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+fn prefix_lines(prefix: &str, text: &str) -> String {
+    text.lines()
+        .map(|line| format!("{}{}\n", prefix, line))
+        .collect()
+}
+
+/// Quote a string for safe use as a single POSIX shell word.
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{basic_auth, method, path};
+    use reqwest::header::HeaderName;
+    use wiremock::matchers::{basic_auth, body_string, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[test]
@@ -113,6 +795,76 @@ mod tests {
         assert_eq!(config.tenant, "tenant");
     }
 
+    #[test]
+    fn warns_about_swapped_credentials() {
+        let config = |username: &str, password: &str, tenant: &str| {
+            Config::new(
+                username.to_string(),
+                password.to_string(),
+                tenant.to_string(),
+            )
+        };
+
+        assert!(config("alice", "s3cret-Pa55", "acme").warnings().is_empty());
+
+        let warnings = config("acme", "acme", "acme").warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains(r#"ANTITHESIS_USERNAME "acme" looks like a tenant"#));
+        assert!(warnings[1].contains("ANTITHESIS_PASSWORD is the same"));
+
+        let warnings = config("acme.antithesis.com", "pw", "acme.antithesis.com").warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].contains("should be just the tenant name"));
+    }
+
+    #[test]
+    fn trace_redacts_authorization() {
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, "http://example.com").unwrap();
+        let request = api
+            .post("/launch/basic_test")
+            .header("content-type", "application/json")
+            .body(r#"{"params":{}}"#)
+            .build()
+            .unwrap();
+
+        let trace = trace_request(&request);
+
+        assert!(trace.starts_with("> POST http://example.com/launch/basic_test\n"));
+        assert!(trace.contains("> Authorization: [REDACTED]\n"));
+        assert!(trace.contains("> Content-Type: application/json\n"));
+        assert!(trace.ends_with(">\n> {\"params\":{}}\n"));
+        assert!(!trace.contains("dXNlcjpwYXNz"));
+    }
+
+    #[tokio::test]
+    async fn get_url_sends_credentials_only_to_the_api_origin() {
+        let api_server = MockServer::start().await;
+        let other_server = MockServer::start().await;
+        for server in [&api_server, &other_server] {
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(200))
+                .mount(server)
+                .await;
+        }
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, api_server.uri()).unwrap();
+        api.get_url(&format!("{}/schema.json", api_server.uri()))
+            .send()
+            .await
+            .unwrap();
+        api.get_url(&format!("{}/schema.json", other_server.uri()))
+            .send()
+            .await
+            .unwrap();
+
+        let has_auth =
+            |requests: Vec<wiremock::Request>| requests[0].headers.contains_key(AUTHORIZATION);
+        assert!(has_auth(api_server.received_requests().await.unwrap()));
+        assert!(!has_auth(other_server.received_requests().await.unwrap()));
+    }
+
     #[tokio::test]
     async fn api_uses_basic_auth() {
         let mock_server = MockServer::start().await;
@@ -133,10 +885,509 @@ mod tests {
         assert_eq!(response.status(), 200);
     }
 
+    #[tokio::test]
+    async fn post_json_signs_body() {
+        let mock_server = MockServer::start().await;
+
+        let body = r#"{"antithesis.duration":"30"}"#;
+        Mock::given(method("POST"))
+            .and(path("/launch/basic_test"))
+            .and(header(
+                "X-Gateway-Signature",
+                "b8e655c93389cdbf2d6272245588b6db5a491d94d4af9932ebe197ec55a50ee5",
+            ))
+            .and(body_string(body))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let signer = Signer::new("secret", HeaderName::from_static("x-gateway-signature"));
+        let api = AntithesisApi::with_base_url(config, mock_server.uri())
+            .unwrap()
+            .with_signer(Some(signer));
+
+        let response = api
+            .post_json("/launch/basic_test", body.to_string())
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+    }
+
+    const TEST_CA: &str = "-----BEGIN CERTIFICATE-----
+MIIBiDCCAS+gAwIBAgIUflWhIXxOGIB/+GxxCKOtVh3Hs6MwCgYIKoZIzj0EAwIw
+GTEXMBUGA1UEAwwOc25vdXR5IHRlc3QgQ0EwIBcNMjYxMDE2MTA1OTIzWhgPMjEy
+NjA5MjIxMDU5MjNaMBkxFzAVBgNVBAMMDnNub3V0eSB0ZXN0IENBMFkwEwYHKoZI
+zj0CAQYIKoZIzj0DAQcDQgAEYVWctEYxdieOq0rT2ZXClQv76HWi54ozUmAFa6Pi
+0nZVTEko7+Gda6ElbbJbhE/aiTAE59zn9i2ODESdmLBjOKNTMFEwHQYDVR0OBBYE
+FESqQ3mfUsqN0QzthAHElDVqBRUTMB8GA1UdIwQYMBaAFESqQ3mfUsqN0QzthAHE
+lDVqBRUTMA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDRwAwRAIgf/pGAfcM
++25xGSZSgz6Unq8CQ9fd84l1xO4+juOWL20CIAoUQktjqHwHe5ywV8o469Am//38
+HnnWg9hQdxXugZc8
+-----END CERTIFICATE-----
+";
+
+    fn pem_file(contents: &str) -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), contents).unwrap();
+        file
+    }
+
+    #[test]
+    fn ca_bundle_is_added_to_client() {
+        let file = pem_file(TEST_CA);
+        let certificates = load_ca_bundle(file.path()).unwrap();
+        assert_eq!(certificates.len(), 1);
+        add_root_certificates(Timeouts::default().client_builder(), certificates)
+            .build()
+            .unwrap();
+    }
+
+    #[test]
+    fn malformed_ca_bundle_is_rejected() {
+        let truncated = TEST_CA.replace("HnnWg9hQdxXugZc8\n", "");
+        let file = pem_file(&truncated);
+        let err = load_ca_bundle(file.path()).unwrap_err().to_string();
+        assert!(err.starts_with(&format!("invalid CA bundle {}: ", file.path().display())));
+
+        let file = pem_file("not a certificate\n");
+        let err = load_ca_bundle(file.path()).unwrap_err().to_string();
+        assert!(err.ends_with("no PEM certificates found"));
+    }
+
+    #[test]
+    fn with_query_encodes_pairs() {
+        assert_eq!(with_query("/launch/basic_test", &[]), "/launch/basic_test");
+
+        let query = [
+            "priority=high".parse().unwrap(),
+            "note=a&b c".parse().unwrap(),
+        ];
+        assert_eq!(
+            with_query("/launch/basic_test", &query),
+            "/launch/basic_test?priority=high&note=a%26b+c"
+        );
+
+        assert!("=high".parse::<QueryParam>().is_err());
+        assert!("priority".parse::<QueryParam>().is_err());
+    }
+
+    #[tokio::test]
+    async fn query_params_reach_server() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/launch/basic_test"))
+            .and(query_param("priority", "high"))
+            .and(query_param("note", "a&b c"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+        let query = [
+            "priority=high".parse().unwrap(),
+            "note=a&b c".parse().unwrap(),
+        ];
+
+        let response = api
+            .post(&with_query("/launch/basic_test", &query))
+            .send()
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn gzip_responses_are_decoded() {
+        use flate2::Compression;
+        use flate2::write::GzEncoder;
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(br#"{"status": "ok"}"#).unwrap();
+        let gzipped = encoder.finish().unwrap();
+
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/gzip"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "gzip")
+                    .set_body_bytes(gzipped),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/brotli"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("Content-Encoding", "br")
+                    .set_body_bytes(b"\x0b\x02\x80{}\x03".to_vec()),
+            )
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+
+        let response = api.get("/gzip").send().await.unwrap();
+        check_content_encoding(&response).unwrap();
+        assert_eq!(response.text().await.unwrap(), r#"{"status": "ok"}"#);
+
+        let response = api.get("/brotli").send().await.unwrap();
+        let err = check_content_encoding(&response).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(r#"unsupported Content-Encoding "br""#)
+        );
+    }
+
+    #[tokio::test]
+    async fn cloned_api_serves_concurrent_requests() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/test"))
+            .and(basic_auth("user", "pass"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(4)
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let api = api.clone();
+                tokio::spawn(async move { api.get("/test").send().await.unwrap().status() })
+            })
+            .collect();
+
+        for handle in handles {
+            assert_eq!(handle.await.unwrap(), 200);
+        }
+    }
+
+    #[tokio::test]
+    async fn launch_retries_reuse_the_idempotency_key() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+        let policy = RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+        api.send_with_retry(api.post_launch("/launch", "{}".to_string()), &policy)
+            .await
+            .unwrap();
+
+        let keys: Vec<_> = mock_server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|request| request.headers["idempotency-key"].clone())
+            .collect();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0], keys[1]);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_retries_server_errors() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/launch"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/launch"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+
+        let (response, attempts) = api
+            .send_with_retry(api.post("/launch").body("{}"), &policy)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(attempts, 3);
+    }
+
+    #[tokio::test]
+    async fn send_with_retry_gives_up_after_max_retries() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(2)
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+        let policy = RetryPolicy {
+            max_retries: 1,
+            base_delay: Duration::from_millis(1),
+            ..RetryPolicy::default()
+        };
+
+        let (response, attempts) = api
+            .send_with_retry(api.post("/launch"), &policy)
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), 500);
+        assert_eq!(attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn retry_budget_is_shared() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(5)
+            .mount(&mock_server)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, mock_server.uri()).unwrap();
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            budget: Some(RetryBudget::new(2)),
+            ..RetryPolicy::default()
+        };
+
+        // Three requests with up to 3 retries each, but only 2 retries in total
+        let mut attempts = Vec::new();
+        for _ in 0..3 {
+            let (response, n) = api
+                .send_with_retry(api.post("/launch"), &policy)
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 500);
+            attempts.push(n);
+        }
+
+        assert_eq!(attempts, [3, 1, 1]);
+    }
+
+    #[test]
+    fn retry_delay_doubles() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+    }
+
+    #[test]
+    fn retry_delay_is_capped() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(5),
+            ..RetryPolicy::default()
+        };
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(4), Duration::from_secs(5));
+        assert_eq!(policy.delay(40), Duration::from_secs(5));
+    }
+
+    fn jittered_delays(jitter: Jitter, attempt: u32) -> Vec<Duration> {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            jitter,
+            ..RetryPolicy::default()
+        };
+        let mut rng = JitterRng(42);
+        (0..1000)
+            .map(|_| policy.delay_with(attempt, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn no_jitter_is_exact() {
+        let delays = jittered_delays(Jitter::None, 3);
+        assert!(delays.iter().all(|&d| d == Duration::from_millis(400)));
+    }
+
+    #[test]
+    fn full_jitter_spans_zero_to_backoff() {
+        let delays = jittered_delays(Jitter::Full, 3);
+        assert!(delays.iter().all(|&d| d < Duration::from_millis(400)));
+        assert!(delays.iter().any(|&d| d < Duration::from_millis(40)));
+        assert!(delays.iter().any(|&d| d > Duration::from_millis(360)));
+    }
+
+    #[test]
+    fn equal_jitter_keeps_half_the_backoff() {
+        let delays = jittered_delays(Jitter::Equal, 3);
+        assert!(delays.iter().all(|&d| d >= Duration::from_millis(200)));
+        assert!(delays.iter().all(|&d| d < Duration::from_millis(400)));
+        assert!(delays.iter().any(|&d| d < Duration::from_millis(220)));
+        assert!(delays.iter().any(|&d| d > Duration::from_millis(380)));
+    }
+
+    #[test]
+    fn jitter_is_deterministic_for_a_seed() {
+        assert_eq!(
+            jittered_delays(Jitter::Full, 2),
+            jittered_delays(Jitter::Full, 2)
+        );
+    }
+
+    #[test]
+    fn curl_command_uses_auth_placeholder() {
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, "http://example.com").unwrap();
+        let body = r#"{"params":{"antithesis.description":"it's"}}"#;
+
+        let curl = api.curl_command("/launch/basic_test", body);
+
+        assert!(curl.starts_with("curl -X POST 'http://example.com/launch/basic_test'"));
+        assert!(curl.contains(r#"-u "$ANTITHESIS_USERNAME:$ANTITHESIS_PASSWORD""#));
+        assert!(curl.contains(r#"-d '{"params":{"antithesis.description":"it'\''s"}}'"#));
+        assert!(!curl.contains("pass\""));
+    }
+
+    #[test]
+    fn with_base_url_rejects_url_without_scheme() {
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let err = AntithesisApi::with_base_url(config.clone(), "localhost:8080")
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains("invalid base URL \"localhost:8080\"")
+        );
+        assert!(AntithesisApi::with_base_url(config, "/api/v1").is_err());
+    }
+
+    #[test]
+    fn with_base_url_accepts_absolute_url() {
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        assert!(AntithesisApi::with_base_url(config, "https://localhost:8080/api").is_ok());
+    }
+
     #[test]
     fn with_base_url_trims_trailing_slash() {
         let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
         let api = AntithesisApi::with_base_url(config, "http://example.com/").unwrap();
         assert_eq!(api.base_url(), "http://example.com");
     }
+
+    #[tokio::test]
+    async fn redirects_to_another_host_drop_credentials() {
+        let target = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/launch"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&target)
+            .await;
+
+        let origin = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/launch"))
+            .and(basic_auth("user", "pass"))
+            .respond_with(
+                ResponseTemplate::new(307)
+                    .insert_header("Location", format!("{}/launch", target.uri())),
+            )
+            .mount(&origin)
+            .await;
+
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        let api = AntithesisApi::with_base_url(config, origin.uri()).unwrap();
+        let response = api.post("/launch").body("{}").send().await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        let requests = target.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(!requests[0].headers.contains_key(AUTHORIZATION));
+    }
+
+    #[tokio::test]
+    async fn zero_max_redirects_returns_the_redirect() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/elsewhere"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        config.max_redirects = 0;
+        let api = AntithesisApi::with_base_url(config, server.uri()).unwrap();
+        let response = api.post("/launch").send().await.unwrap();
+        assert_eq!(response.status(), 302);
+    }
+
+    #[tokio::test]
+    async fn client_gets_a_separate_connect_timeout() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(300)))
+            .mount(&mock_server)
+            .await;
+        let get = |timeouts: Timeouts| {
+            let api =
+                AntithesisApi::unauthenticated(mock_server.uri(), timeouts, DEFAULT_MAX_REDIRECTS)
+                    .unwrap();
+            async move { api.get("/slow").send().await }
+        };
+
+        // A short connect timeout doesn't cut a slow response short
+        let response = get(Timeouts {
+            total: Duration::from_secs(5),
+            connect: Duration::from_millis(50),
+        })
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The total timeout still covers the whole request
+        let err = get(Timeouts {
+            total: Duration::from_millis(100),
+            connect: Duration::from_secs(5),
+        })
+        .await
+        .unwrap_err();
+        assert!(err.is_timeout(), "{}", err);
+    }
 }
@@ -0,0 +1,150 @@
+//! `snouty apply` manifests: a webhook, its params, and launch options in one
+//! YAML file.
+//!
+//! ```yaml
+//! webhook: basic_test
+//! after: 5m
+//! recipients: team@example.com
+//! params:
+//!   antithesis.duration: 30
+//!   antithesis.images: app:latest
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use log::debug;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::error::{Error, Result};
+use crate::params::{Params, Source};
+
+/// A parsed apply manifest.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApplyManifest {
+    /// The webhook to launch.
+    pub webhook: String,
+    /// How long to wait before launching, like `--after`.
+    #[serde(default)]
+    after: Option<String>,
+    /// Shorthand for the `antithesis.report.recipients` param.
+    #[serde(default)]
+    recipients: Option<String>,
+    /// Params by dotted key. Numbers and booleans are sent as strings.
+    #[serde(default)]
+    params: BTreeMap<String, Value>,
+}
+
+impl ApplyManifest {
+    /// Read and check a manifest file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| Error::InvalidArgs(format!("failed to read {}: {}", path.display(), e)))?;
+        let manifest = Self::parse(&contents).map_err(|message| {
+            Error::InvalidArgs(format!("invalid manifest {}: {}", path.display(), message))
+        })?;
+        debug!(
+            "read manifest {} for webhook {}",
+            path.display(),
+            manifest.webhook
+        );
+        Ok(manifest)
+    }
+
+    /// Parse a manifest, checking its shape.
+    pub fn parse(contents: &str) -> std::result::Result<Self, String> {
+        let manifest: Self = serde_yaml::from_str(contents).map_err(|e| e.to_string())?;
+        if manifest.webhook.trim().is_empty() {
+            return Err("webhook is empty".to_string());
+        }
+        if let Some(after) = &manifest.after {
+            humantime::parse_duration(after).map_err(|e| format!("after {:?}: {}", after, e))?;
+        }
+        for (key, value) in &manifest.params {
+            if !matches!(value, Value::String(_) | Value::Number(_) | Value::Bool(_)) {
+                return Err(format!(
+                    "param {} must be a string, number, or boolean",
+                    key
+                ));
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// The `after` delay, if one was given.
+    pub fn after(&self) -> Option<Duration> {
+        let after = self.after.as_deref()?;
+        Some(humantime::parse_duration(after).expect("checked when parsed"))
+    }
+
+    /// The params, including `recipients`, as coming from a file.
+    pub fn params(&self) -> Params {
+        let mut params = Params::default();
+        if let Some(recipients) = &self.recipients {
+            params.insert_from("antithesis.report.recipients", recipients, Source::File);
+        }
+        for (key, value) in &self.params {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                Value::Number(n) => n.to_string(),
+                Value::Bool(b) => b.to_string(),
+                _ => unreachable!("checked when parsed"),
+            };
+            params.insert_from(key, value, Source::File);
+        }
+        params
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_manifest() {
+        let manifest = ApplyManifest::parse(
+            r#"
+webhook: basic_test
+after: 90s
+recipients: team@example.com
+params:
+  antithesis.duration: 30
+  antithesis.is_ephemeral: true
+  antithesis.images: app:latest
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(manifest.webhook, "basic_test");
+        assert_eq!(manifest.after(), Some(Duration::from_secs(90)));
+        let params = manifest.params();
+        assert_eq!(
+            params.to_value(),
+            serde_json::json!({
+                "antithesis.report.recipients": "team@example.com",
+                "antithesis.duration": "30",
+                "antithesis.images": "app:latest",
+                "antithesis.is_ephemeral": "true",
+            })
+        );
+        assert_eq!(params.source("antithesis.duration"), Some(Source::File));
+    }
+
+    #[test]
+    fn rejects_bad_shapes() {
+        let err = |contents: &str| ApplyManifest::parse(contents).unwrap_err();
+
+        assert!(err("params: {}").contains("missing field `webhook`"));
+        assert!(err("webhook: basic_test\nrepeat: 3").contains("unknown field `repeat`"));
+        assert!(err("webhook: basic_test\nafter: soon").contains("after \"soon\""));
+        assert!(err("webhook: basic_test\nwait: 30m").contains("unknown field `wait`"));
+        assert_eq!(
+            err("webhook: basic_test\nparams:\n  antithesis.images: [a, b]"),
+            "param antithesis.images must be a string, number, or boolean"
+        );
+    }
+}
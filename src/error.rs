@@ -1,4 +1,7 @@
 use std::env::VarError;
+use std::path::PathBuf;
+
+use reqwest::Method;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
@@ -11,17 +14,114 @@ pub enum Error {
         source: VarError,
     },
 
-    #[error("HTTP request failed: {0:?}")]
+    #[error("HTTP request failed: {}", describe_request_error(.0))]
     Request(#[from] reqwest::Error),
 
-    #[error("API error: {status} - {message}")]
-    Api { status: u16, message: String },
+    #[error("API error: {status} on {method} {path} - {message}")]
+    Api {
+        status: u16,
+        method: Method,
+        path: String,
+        message: String,
+    },
+
+    #[error("{failed} of {total} launches failed")]
+    LaunchesFailed { failed: usize, total: usize },
+
+    #[error("unexpected response: {0}")]
+    UnexpectedResponse(String),
+
+    #[error("invalid config file {}: {message}", .path.display())]
+    Config { path: PathBuf, message: String },
+
+    #[error("invalid CA bundle {}: {message}", .path.display())]
+    CaBundle { path: PathBuf, message: String },
 
     #[error("invalid arguments: {0}")]
     InvalidArgs(String),
 
+    #[error("{0} (warnings are errors with --strict-warnings)")]
+    StrictWarning(String),
+
+    #[error("invalid schema: {0}")]
+    InvalidSchema(String),
+
     #[error("validation failed:\n  {}", .0.join("\n  "))]
     ValidationFailed(Vec<String>),
+
+    #[error("validation failed: {0} error{s}", s = if *.0 == 1 { "" } else { "s" })]
+    ValidationSummary(usize),
+
+    #[error("--exec command {command:?} failed ({status})")]
+    ExecFailed {
+        command: String,
+        status: std::process::ExitStatus,
+    },
+}
+
+impl Error {
+    /// The process exit code for this error: the --exec command's own code
+    /// when it failed, otherwise 1.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::ExecFailed { status, .. } => status.code().unwrap_or(1),
+            _ => 1,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+/// Describe a request error with a hint about the likely cause.
+///
+/// reqwest's own messages are terse ("error sending request"), and the useful
+/// detail is buried in the source chain, so look there for DNS, connection,
+/// and TLS failures.
+fn describe_request_error(e: &reqwest::Error) -> String {
+    let host = e
+        .url()
+        .and_then(|url| url.host_str())
+        .unwrap_or("the server")
+        .to_string();
+
+    let mut causes = Vec::new();
+    let mut io_kind = None;
+    let mut source = std::error::Error::source(e);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            io_kind.get_or_insert(io.kind());
+        }
+        causes.push(err.to_string());
+        source = err.source();
+    }
+    let detail = causes.join(": ").to_lowercase();
+
+    let hint = if e.is_timeout() {
+        Some(format!("timed out waiting for {}", host))
+    } else if detail.contains("dns error") || detail.contains("failed to lookup address") {
+        Some(format!(
+            "could not resolve {}; check ANTITHESIS_TENANT or ANTITHESIS_BASE_URL",
+            host
+        ))
+    } else if io_kind == Some(std::io::ErrorKind::ConnectionRefused)
+        || detail.contains("connection refused")
+    {
+        Some(format!(
+            "connection refused by {}; check that ANTITHESIS_BASE_URL points at a running server",
+            host
+        ))
+    } else if detail.contains("certificate") || detail.contains("tls") || detail.contains("ssl") {
+        Some(format!("TLS handshake with {} failed", host))
+    } else {
+        None
+    };
+
+    let mut message = e.to_string();
+    if !causes.is_empty() {
+        message = format!("{}: {}", message, causes.join(": "));
+    }
+    match hint {
+        Some(hint) => format!("{} ({})", hint, message),
+        None => message,
+    }
+}
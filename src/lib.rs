@@ -0,0 +1,22 @@
+//! The modules behind the `snouty` CLI. [`params`] and [`error`] can also be
+//! used as a library, e.g. to validate params against the bundled schema.
+
+pub mod api;
+pub mod apply;
+pub mod error;
+pub mod logging;
+pub mod manifest;
+pub mod moment;
+pub mod netrc;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod params;
+pub mod recipients;
+pub mod report;
+pub mod response;
+pub mod schema;
+pub mod settings;
+pub mod signing;
+pub mod template;
+pub mod warnings;
+pub mod watch;
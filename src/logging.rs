@@ -0,0 +1,34 @@
+//! Log output setup.
+
+use std::io::Write;
+
+use clap::ValueEnum;
+
+/// How log records are written to stderr.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum LogFormat {
+    /// Human-readable lines (env_logger's default format).
+    #[default]
+    Text,
+    /// One JSON object per line with `timestamp`, `level`, `target`, and
+    /// `message`, for log aggregators.
+    Json,
+}
+
+/// Initialize the global logger. `RUST_LOG` controls the level in both formats.
+pub fn init(format: LogFormat) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if format == LogFormat::Json {
+        builder.format(|buf, record| {
+            // Keys in sorted order, like the rest of snouty's JSON output
+            let line = serde_json::json!({
+                "level": record.level().as_str(),
+                "message": record.args().to_string(),
+                "target": record.target(),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    builder.init();
+}
@@ -1,23 +1,67 @@
-pub mod api;
-pub mod error;
-pub mod moment;
-pub mod params;
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
 
-use std::io::{self, ErrorKind, Read};
-use std::process::Command;
-
-use chrono::{Duration, Local};
-use clap::{Parser, Subcommand};
+use chrono::{DateTime, Duration, Local};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use log::{debug, info};
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde_json::{Map, Value};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use snouty::api::{
+    AntithesisApi, Config, EnvDefaults, Jitter, QueryParam, RetryBudget, RetryPolicy, Timeouts,
+    check_body_size, with_query,
+};
+use snouty::apply::ApplyManifest;
+use snouty::error::{Error, Result};
+use snouty::logging::LogFormat;
+#[cfg(feature = "otel")]
+use snouty::otel;
+use snouty::params::{ArgsOptions, EnvVar, Label, Params, Schema, Source, ValidateOptions};
+use snouty::recipients::DnsMxLookup;
+use snouty::response::{ExpectField, FieldPointer};
+use snouty::settings::Settings;
+use snouty::signing::Signer;
+use snouty::watch::FileWatcher;
+use snouty::{
+    api, logging, manifest, moment, params, recipients, report, schema, signing, template, warnings,
+};
+
+/// Where `run --local` sends launches when no URL is given.
+const DEFAULT_LOCAL_URL: &str = "http://localhost:8080";
 
-use crate::api::AntithesisApi;
-use crate::error::{Error, Result};
-use crate::params::Params;
+/// Minutes after a run ends that its report email is expected by default.
+const DEFAULT_EMAIL_BUFFER_MINS: u32 = 10;
 
 #[derive(Parser)]
 #[command(name = "snouty")]
 #[command(about = "CLI for the Antithesis API", long_about = None)]
 struct Cli {
+    /// Log format for RUST_LOG output
+    #[arg(
+        long,
+        global = true,
+        env = "SNOUTY_LOG_FORMAT",
+        value_enum,
+        default_value_t = LogFormat::Text
+    )]
+    log_format: LogFormat,
+
+    /// Treat warnings (e.g. suspicious credentials or recipients) as errors
+    #[arg(long, global = true)]
+    strict_warnings: bool,
+
+    /// Config file profile to take the tenant and base URL from
+    #[arg(long, global = true, env = "SNOUTY_ENV")]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -35,19 +79,7 @@ Example:
     --antithesis.images app:latest \
     --antithesis.duration 30 \
     --antithesis.report.recipients "team@example.com""#)]
-    Run {
-        /// Webhook endpoint name (e.g., basic_test, basic_k8s_test)
-        #[arg(short, long)]
-        webhook: String,
-
-        /// Read parameters from stdin (JSON or Moment.from format)
-        #[arg(long)]
-        stdin: bool,
-
-        /// Parameters as `--key value` pairs
-        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
-        args: Vec<String>,
-    },
+    Run(RunArgs),
     /// Launch a debugging session
     #[command(long_about = r#"Launch a debugging session
 
@@ -61,179 +93,1784 @@ Using CLI arguments:
 Using Moment.from (copy from triage report):
   echo 'Moment.from({ session_id: "...", input_hash: "...", vtime: ... })' | \
     snouty debug --stdin --antithesis.report.recipients "team@example.com""#)]
-    Debug {
-        /// Read parameters from stdin (JSON or Moment.from format)
-        #[arg(long)]
-        stdin: bool,
-
-        /// Parameters as `--key value` pairs
-        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
-        args: Vec<String>,
-    },
+    Debug(DebugArgs),
+    /// Launch a test run described by a YAML manifest
+    #[command(long_about = r#"Launch a test run described by a YAML manifest
+
+The manifest names the webhook and its params, and optionally a delay before
+launching (after, like --after) and report recipients. Options and params on the command line
+override it.
+
+Example manifest:
+  webhook: basic_test
+  after: 5m
+  recipients: team@example.com
+  params:
+    antithesis.duration: 30
+    antithesis.images: app:latest
+
+  snouty apply nightly.yaml"#)]
+    Apply(ApplyArgs),
+    /// Show the key-by-key differences between two params files
+    #[command(
+        long_about = r#"Show the key-by-key differences between two params files
+
+Each file is a JSON (or JSON5), TOML, or YAML params object, detected from its
+extension or, failing that, by trying each parser in turn. Added keys are
+marked +, removed keys -, and changed keys ~; sensitive values are redacted.
+
+Example:
+  snouty diff old-params.json new-params.json"#
+    )]
+    Diff(DiffArgs),
     /// Print version information
     Version,
     /// Check for and install updates
     Update,
 }
 
+#[derive(Args, Clone)]
+struct RunArgs {
+    /// Webhook endpoint name (e.g., basic_test, basic_k8s_test); defaults to a
+    /// `webhook` key in the params, then `default_webhook` from the config file
+    #[arg(short, long)]
+    webhook: Option<String>,
+
+    /// Read parameters from stdin (JSON or Moment.from format)
+    #[arg(long)]
+    stdin: bool,
+
+    /// POST this file's JSON verbatim instead of building a body from params
+    #[arg(
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["stdin", "labels", "env", "args", "no_envelope", "only_validate"]
+    )]
+    body_file: Option<PathBuf>,
+
+    /// Set antithesis.images from the images in a docker-compose or Kubernetes
+    /// YAML file; an explicit --antithesis.images still wins
+    #[arg(long, value_name = "PATH", conflicts_with = "body_file")]
+    images_from: Option<PathBuf>,
+
+    /// Launch with params from this file (JSON, TOML, or YAML), then relaunch
+    /// each time it changes, until Ctrl-C
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["stdin", "body_file"])]
+    watch_file: Option<PathBuf>,
+
+    /// Accept durations like `30m` or `1h` for antithesis.duration
+    #[arg(long)]
+    humanize_duration: bool,
+
+    /// Attach a label to the run as `antithesis.labels.<key>` (repeatable)
+    #[arg(long = "label", value_name = "KEY=VALUE")]
+    labels: Vec<Label>,
+
+    /// Pass an environment variable to the test as `antithesis.env.<KEY>`
+    /// (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    env: Vec<EnvVar>,
+
+    /// Launch against a locally running test-composer simulation instead of
+    /// the Antithesis API, sending no credentials
+    #[arg(
+        long,
+        value_name = "URL",
+        num_args = 0..=1,
+        default_missing_value = DEFAULT_LOCAL_URL
+    )]
+    local: Option<String>,
+
+    /// Minutes to add to antithesis.duration when estimating when the report
+    /// email arrives [default: 10, or `email_buffer_mins` from the config file]
+    #[arg(long, value_name = "N")]
+    email_buffer_mins: Option<u32>,
+
+    #[command(flatten)]
+    batch: RepeatOpts,
+
+    #[command(flatten)]
+    launch: LaunchOpts,
+
+    /// Parameters as `--key value` or `--key=value` pairs
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+#[derive(Args)]
+struct ApplyArgs {
+    /// The manifest to launch
+    manifest: PathBuf,
+
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+#[derive(Args)]
+struct DebugArgs {
+    /// Read parameters from stdin (JSON or Moment.from format)
+    #[arg(long)]
+    stdin: bool,
+
+    /// Take the moment to debug from this triage report (fetched as JSON);
+    /// params on the command line override it
+    #[arg(
+        long,
+        value_name = "URL",
+        conflicts_with_all = ["stdin", "params_fds", "only_validate"]
+    )]
+    report_url: Option<String>,
+
+    /// Print the debugged moment as a Moment.from string on success
+    #[arg(long)]
+    print_moment: bool,
+
+    #[command(flatten)]
+    launch: LaunchOpts,
+
+    /// Parameters as `--key value` or `--key=value` pairs
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    args: Vec<String>,
+}
+
+/// Input formats for params read from stdin.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ParamsFormat {
+    /// Moment.from(...) if it looks like one (debug only), otherwise JSON
+    Auto,
+    /// A JSON (or JSON5) object
+    Json,
+    /// A Moment.from(...) copied from a triage report (debug only)
+    Moment,
+    /// A TOML document; tables are flattened to dotted keys
+    Toml,
+}
+
+/// The order of the params in the request body.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BodyKeyOrder {
+    /// Sorted by key, so the same params always give the same body
+    Sorted,
+    /// The order the params were first given in: the environment, stdin,
+    /// params files, then the command line
+    Insertion,
+}
+
+/// Formats for params files.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ParamFileFormat {
+    /// From the extension (.json, .json5, .toml, .yaml, .yml); for any other
+    /// file, the first of JSON, TOML, and YAML that parses
+    Auto,
+    /// A JSON (or JSON5) object
+    Json,
+    /// A TOML document; tables are flattened to dotted keys
+    Toml,
+    /// A YAML mapping; nested mappings are flattened to dotted keys
+    Yaml,
+}
+
+impl ParamFileFormat {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Json => "JSON",
+            Self::Toml => "TOML",
+            Self::Yaml => "YAML",
+        }
+    }
+
+    fn parse(self, contents: &str) -> Result<Params> {
+        match self {
+            Self::Auto => unreachable!("resolved before parsing"),
+            Self::Json => Params::from_json5(contents),
+            Self::Toml => Params::from_toml(contents),
+            Self::Yaml => Params::from_yaml(contents),
+        }
+    }
+}
+
+/// How `--repeat` reports each launch.
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BatchOutput {
+    /// A `Run N/M` line on stderr per launch, then a summary
+    Text,
+    /// A JSON object per launch on stdout as it finishes, with `index`,
+    /// `webhook`, `status`, and `error`
+    Ndjson,
+}
+
+#[derive(Args)]
+struct DiffArgs {
+    /// The original params file
+    a: PathBuf,
+
+    /// The params file to compare it with
+    b: PathBuf,
+
+    /// Format of the params files, instead of detecting it
+    #[arg(long, value_enum, default_value_t = ParamFileFormat::Auto)]
+    param_file_format: ParamFileFormat,
+}
+
+/// Options for launching the same run several times.
+#[derive(Args, Clone)]
+struct RepeatOpts {
+    /// Launch the same run this many times
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..),
+        conflicts_with = "response_out"
+    )]
+    repeat: u32,
+
+    /// How many repeated launches to send at once
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    concurrency: u32,
+
+    /// With --repeat, print only the `launched=N failed=N` summary line
+    #[arg(long, requires = "repeat")]
+    count_only: bool,
+
+    /// With --repeat, list whether each launch succeeded before the summary
+    #[arg(long, requires = "repeat", conflicts_with = "count_only")]
+    verbose: bool,
+
+    /// With --repeat, the total number of retries shared by all launches;
+    /// once spent, failing launches aren't retried
+    #[arg(long, value_name = "RETRIES", requires = "repeat")]
+    batch_retry_budget: Option<u32>,
+
+    /// With --repeat, how to report each launch
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = BatchOutput::Text,
+        requires = "repeat",
+        conflicts_with = "count_only"
+    )]
+    output: BatchOutput,
+}
+
+/// Options shared by the commands that call a launch webhook.
+#[derive(Args, Clone)]
+struct LaunchOpts {
+    /// List the known parameters and their descriptions, then exit
+    #[arg(long)]
+    list_params: bool,
+
+    /// Print the resolved credentials (masked), base URL, timeouts, and retry
+    /// settings, with where each came from, then exit
+    #[arg(long)]
+    print_config: bool,
+
+    /// Validate the params and exit; needs no credentials and makes no requests
+    #[arg(long, conflicts_with = "schema")]
+    only_validate: bool,
+
+    /// Take params from either --stdin or the command line, never both
+    #[arg(long)]
+    no_merge: bool,
+
+    /// Read params from environment variables with this prefix, e.g.
+    /// `SNOUTY_PARAM_` turns `SNOUTY_PARAM_ANTITHESIS__DURATION` into
+    /// antithesis.duration (`__` becomes `.`). Everything else overrides them
+    #[arg(long, value_name = "PREFIX")]
+    params_from_env: Option<String>,
+
+    /// Also read params from this file descriptor, e.g. `--params-fd 3
+    /// 3< <(...)` (repeatable; Unix only). They're merged in order after
+    /// --stdin and before the command line
+    #[arg(long = "params-fd", value_name = "FD", conflicts_with = "no_merge")]
+    params_fds: Vec<u32>,
+
+    /// Report only the first schema validation error (faster for large params)
+    #[arg(long)]
+    first_error: bool,
+
+    /// On validation failure, print only `validation failed: N errors`
+    #[arg(long)]
+    summary_only: bool,
+
+    /// Write every validation error to this file, one per line, when
+    /// validation fails
+    #[arg(long, value_name = "PATH")]
+    error_file: Option<PathBuf>,
+
+    /// How to parse --stdin input instead of detecting it
+    #[arg(long, value_enum, default_value_t = ParamsFormat::Auto, requires = "stdin")]
+    params_format: ParamsFormat,
+
+    /// How long to wait for DNS and the connection to the API, separate from
+    /// the 30s limit on the whole request
+    #[arg(
+        long,
+        env = "ANTITHESIS_CONNECT_TIMEOUT",
+        value_name = "DURATION",
+        default_value = "10s",
+        value_parser = humantime::parse_duration
+    )]
+    connect_timeout: std::time::Duration,
+
+    /// Follow at most this many redirects (0 to not follow any). Credentials
+    /// are never sent on to a different host
+    #[arg(long, default_value_t = api::DEFAULT_MAX_REDIRECTS)]
+    max_redirects: usize,
+
+    /// Header to send the HMAC-SHA256 signature of the body in, when
+    /// ANTITHESIS_SIGNING_KEY is set
+    #[arg(
+        long,
+        env = "ANTITHESIS_SIGNATURE_HEADER",
+        value_name = "HEADER",
+        default_value = signing::DEFAULT_SIGNATURE_HEADER
+    )]
+    signature_header: reqwest::header::HeaderName,
+
+    /// Wait this long before sending the request (e.g. 30s, 5m)
+    #[arg(long, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    after: Option<std::time::Duration>,
+
+    /// Refuse to launch if the params preview would show a secret, such as
+    /// the API password or a token value under a key that isn't redacted
+    #[arg(long)]
+    assert_no_leak: bool,
+
+    /// Allow launching against a tenant listed in the config file's
+    /// `protected_tenants`
+    #[arg(long)]
+    confirm_prod: bool,
+
+    /// Fail before launching if this param is missing, in addition to the
+    /// config file's `required_fields` (repeatable)
+    #[arg(long = "require-field", value_name = "KEY")]
+    require_fields: Vec<String>,
+
+    /// Write the merged params to this file as JSON and exit without launching
+    #[arg(long, value_name = "PATH")]
+    params_only: Option<PathBuf>,
+
+    /// Don't redact sensitive values in the --params-only export
+    #[arg(long, requires = "params_only")]
+    include_secrets: bool,
+
+    /// Don't print the params being sent
+    #[arg(long)]
+    no_preview: bool,
+
+    /// Validate against a schema fetched from this URL instead of the bundled one
+    #[arg(long, value_name = "URL")]
+    schema: Option<String>,
+
+    /// Reject param values that begin with `-` (e.g. `--antithesis.duration -30`)
+    #[arg(long)]
+    strict_args: bool,
+
+    /// Prepend this namespace to command-line param keys without a dot, e.g.
+    /// `--param-prefix my.ns --foo bar` sets `my.ns.foo`
+    #[arg(long, value_name = "PREFIX")]
+    param_prefix: Option<String>,
+
+    /// Warn if a report recipient's domain has no MX records (requires DNS)
+    #[arg(long)]
+    check_recipients: bool,
+
+    /// Retry connection errors, 5xx responses, and --retry-on statuses up to
+    /// this many times
+    #[arg(long, default_value_t = 0)]
+    retries: u32,
+
+    /// Also retry these HTTP statuses, e.g. `409,429` (comma-separated)
+    #[arg(
+        long,
+        value_name = "CODES",
+        value_delimiter = ',',
+        value_parser = clap::value_parser!(u16).range(100..=599)
+    )]
+    retry_on: Vec<u16>,
+
+    /// Delay before the first retry; each later retry doubles it
+    #[arg(
+        long,
+        env = "ANTITHESIS_RETRY_BASE_DELAY",
+        value_name = "DURATION",
+        default_value = "500ms",
+        value_parser = humantime::parse_duration
+    )]
+    retry_base_delay: std::time::Duration,
+
+    /// Longest delay between retries
+    #[arg(
+        long,
+        env = "ANTITHESIS_RETRY_MAX_DELAY",
+        value_name = "DURATION",
+        default_value = "30s",
+        value_parser = humantime::parse_duration
+    )]
+    retry_max_delay: std::time::Duration,
+
+    /// Randomness added to retry delays
+    #[arg(long, env = "ANTITHESIS_RETRY_JITTER", value_enum, default_value_t = Jitter::None)]
+    retry_jitter: Jitter,
+
+    /// Send the params map as the whole body, without the `params` wrapper
+    #[arg(long)]
+    no_envelope: bool,
+
+    /// Order of the params in the request body (and the preview), for
+    /// webhooks sensitive to JSON key order
+    #[arg(long, value_enum, default_value_t = BodyKeyOrder::Sorted)]
+    body_key_order: BodyKeyOrder,
+
+    /// Print the full HTTP request and response to stderr (auth redacted)
+    #[arg(long)]
+    trace_request: bool,
+
+    /// Print an equivalent curl command instead of sending the request
+    #[arg(long)]
+    print_curl: bool,
+
+    /// Write the raw response body to this file, or `-` for stdout
+    #[arg(long, value_name = "PATH")]
+    response_out: Option<PathBuf>,
+
+    /// Add a query parameter to the launch request URL (repeatable)
+    #[arg(long = "query", value_name = "KEY=VALUE")]
+    query: Vec<QueryParam>,
+
+    /// Fail unless the JSON response contains this field, even on HTTP 2xx
+    #[arg(long, value_name = "KEY=VALUE")]
+    expect_field: Option<ExpectField>,
+
+    /// Fail if a 2xx response has an empty or whitespace-only body, which can
+    /// mean a misconfigured webhook
+    #[arg(long)]
+    fail_on_empty_response: bool,
+
+    /// Show where each param in the preview came from (cli, stdin, file,
+    /// config, env, or report)
+    #[arg(long)]
+    show_source: bool,
+
+    /// Print just this field of the JSON response to stdout, e.g. `/run_id`
+    #[arg(long, value_name = "JSON_POINTER")]
+    print_field: Option<FieldPointer>,
+
+    /// On success, pipe the raw response body to this shell command, e.g.
+    /// 'jq .run_id'; snouty fails with its exit code if it fails
+    #[arg(long, value_name = "COMMAND")]
+    exec: Option<String>,
+
+    /// Treat this field of the JSON response (e.g. `/data` of an envelope)
+    /// as the response for --print-field, --template, and printing the body
+    #[arg(long, value_name = "JSON_POINTER")]
+    response_root: Option<FieldPointer>,
+
+    /// Print a custom success line, e.g. '{webhook}: launched, eta={eta}'.
+    /// Placeholders: webhook, eta, status, and response.<field> (dotted)
+    #[arg(long, value_name = "FORMAT")]
+    template: Option<String>,
+}
+
+impl LaunchOpts {
+    fn args_options(&self) -> ArgsOptions {
+        ArgsOptions {
+            strict: self.strict_args,
+            prefix: self.param_prefix.clone(),
+        }
+    }
+
+    fn validate_options(&self) -> ValidateOptions {
+        ValidateOptions {
+            first_error: self.first_error,
+        }
+    }
+
+    /// Apply --error-file and --summary-only to the result of validating
+    /// params.
+    fn report_validation(&self, result: Result<()>) -> Result<()> {
+        let Err(Error::ValidationFailed(errors)) = result else {
+            return result;
+        };
+        if let Some(path) = &self.error_file {
+            let mut contents = errors.join("\n");
+            contents.push('\n');
+            fs::write(path, contents).map_err(|e| {
+                Error::InvalidArgs(format!("failed to write {}: {}", path.display(), e))
+            })?;
+        }
+        if self.summary_only {
+            return Err(Error::ValidationSummary(errors.len()));
+        }
+        Err(Error::ValidationFailed(errors))
+    }
+
+    fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.retries,
+            base_delay: self.retry_base_delay,
+            max_delay: self.retry_max_delay,
+            jitter: self.retry_jitter,
+            retry_on: self
+                .retry_on
+                .iter()
+                .map(|&code| StatusCode::from_u16(code).expect("range checked by clap"))
+                .collect(),
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// The launch request body: `{"params": {...}}`, or the bare params map
+    /// with --no-envelope.
+    fn request_body(&self, params: &Params) -> String {
+        let params = Value::Object(self.ordered(params.as_map().clone()));
+        if self.no_envelope {
+            params.to_string()
+        } else {
+            serde_json::json!({ "params": params }).to_string()
+        }
+    }
+
+    /// Put a params map in --body-key-order.
+    fn ordered(&self, mut map: Map<String, Value>) -> Map<String, Value> {
+        if self.body_key_order == BodyKeyOrder::Sorted {
+            map.sort_keys();
+        }
+        map
+    }
+
+    /// Fail if a key from --require-field or the config file's
+    /// `required_fields` is missing from the params.
+    fn check_required_fields(&self, params: &Params, settings: &Settings) -> Result<()> {
+        let missing: BTreeSet<&str> = self
+            .require_fields
+            .iter()
+            .chain(&settings.required_fields)
+            .map(String::as_str)
+            .filter(|key| !params.as_map().contains_key(*key))
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(Error::InvalidArgs(format!(
+            "missing required param{}: {}",
+            if missing.len() == 1 { "" } else { "s" },
+            missing.into_iter().collect::<Vec<_>>().join(", ")
+        )))
+    }
+
+    /// Refuse to launch against one of the config file's `protected_tenants`
+    /// without --confirm-prod.
+    fn check_protected_tenant(&self, api: &AntithesisApi, settings: &Settings) -> Result<()> {
+        if let Some(tenant) = api.tenant()
+            && !self.confirm_prod
+            && settings.protected_tenants.iter().any(|t| t == tenant)
+        {
+            return Err(Error::InvalidArgs(format!(
+                "tenant {} is protected; pass --confirm-prod to launch against it",
+                tenant
+            )));
+        }
+        Ok(())
+    }
+
+    /// With --assert-no-leak, fail if any secret would be shown unredacted.
+    fn check_no_leak(&self, params: &Params) -> Result<()> {
+        if !self.assert_no_leak {
+            return Ok(());
+        }
+        let secrets: Vec<String> = std::env::var("ANTITHESIS_PASSWORD").into_iter().collect();
+        let leaked = params.leaked_keys(&secrets);
+        if leaked.is_empty() {
+            return Ok(());
+        }
+        Err(Error::InvalidArgs(format!(
+            "refusing to launch: the preview would show a secret under {}",
+            leaked.join(", ")
+        )))
+    }
+
+    /// Load the schema to validate against, falling back to the bundled one
+    /// if `--schema` can't be fetched, plus any supplemental schema from the
+    /// config file.
+    async fn load_schema(&self, settings: &Settings, env_defaults: &EnvDefaults) -> Result<Schema> {
+        let schema = match &self.schema {
+            None => Schema::bundled()?,
+            Some(url) => {
+                let api = self.api(env_defaults)?;
+                let cache_dir = schema::default_cache_dir();
+                match schema::fetch(&api, url, cache_dir.as_deref()).await {
+                    Ok(schema) => schema,
+                    // A schema that arrived but doesn't work is a real error,
+                    // not an outage to paper over with the bundled one
+                    Err(e @ Error::InvalidSchema(_)) => return Err(e),
+                    Err(e) => {
+                        warnings::warn(format_args!(
+                            "failed to fetch schema from {}: {}; using the bundled schema",
+                            url, e
+                        ))?;
+                        Schema::bundled()?
+                    }
+                }
+            }
+        };
+        settings.supplement_schema(schema)
+    }
+
+    /// Run the optional pre-flight checks, printing warnings for any problems.
+    async fn preflight(&self, params: &Params) -> Result<()> {
+        if self.check_recipients {
+            let map = params.as_map();
+            let addresses: Vec<&str> = [
+                recipients::RECIPIENTS_KEY,
+                "antithesis.report.recipients.email",
+            ]
+            .iter()
+            .filter_map(|key| map.get(*key).and_then(|v| v.as_str()))
+            .collect();
+            if !addresses.is_empty() {
+                let addresses = addresses.join(";");
+                let warnings = match DnsMxLookup::from_system_conf() {
+                    Ok(resolver) => recipients::check_recipients(&addresses, &resolver).await,
+                    Err(e) => vec![format!("could not check recipients: {}", e)],
+                };
+                for warning in warnings {
+                    warnings::warn(warning)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the params to `--params-only`, if given.
+    ///
+    /// Returns true if the params were exported and the launch should stop.
+    fn export_params(&self, params: &Params) -> Result<bool> {
+        let Some(path) = &self.params_only else {
+            return Ok(false);
+        };
+        let map = if self.include_secrets {
+            params.as_map().clone()
+        } else {
+            params.to_redacted_map()
+        };
+        let json = serde_json::to_string_pretty(&self.ordered(map)).unwrap() + "\n";
+        fs::write(path, json).map_err(|e| {
+            Error::InvalidArgs(format!("failed to write {}: {}", path.display(), e))
+        })?;
+        eprintln!("Wrote params to {}", path.display());
+        Ok(true)
+    }
+
+    /// Write the response body to `--response-out`, if given.
+    fn write_response(&self, body: &str) -> Result<()> {
+        match self.response_out.as_deref() {
+            None => Ok(()),
+            Some(path) if path == Path::new("-") => {
+                println!("{}", body);
+                Ok(())
+            }
+            Some(path) => fs::write(path, body).map_err(|e| {
+                Error::InvalidArgs(format!("failed to write {}: {}", path.display(), e))
+            }),
+        }
+    }
+
+    /// Check `--template` for unknown placeholders before launching.
+    fn check_template(&self) -> Result<()> {
+        match &self.template {
+            Some(format) => template::check(format, |name| {
+                matches!(name, "webhook" | "eta" | "status") || name.starts_with("response.")
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Print the `--template` success line, if one was given.
+    fn print_template(
+        &self,
+        webhook: &str,
+        eta: &DateTime<Local>,
+        status: StatusCode,
+        body: &str,
+    ) -> Result<()> {
+        let Some(format) = &self.template else {
+            return Ok(());
+        };
+        let response: Value = serde_json::from_str(body).unwrap_or(Value::Null);
+        let line = template::render(format, |name| match name {
+            "webhook" => Some(webhook.to_string()),
+            "eta" => Some(eta.format("%b %-d at %-I:%M %p").to_string()),
+            "status" => Some(status.as_u16().to_string()),
+            _ => template::lookup_path(&response, name.strip_prefix("response.")?),
+        })?;
+        println!("{}", line);
+        Ok(())
+    }
+
+    /// The API client, configured from the environment, the profile's
+    /// defaults, and these options.
+    fn api(&self, env_defaults: &EnvDefaults) -> Result<AntithesisApi> {
+        let mut config = Config::from_env_with(env_defaults)?;
+        config.timeouts.connect = self.connect_timeout;
+        config.max_redirects = self.max_redirects;
+        Ok(AntithesisApi::from_config(config)?.with_signer(self.signer()))
+    }
+
+    /// An unauthenticated client for a local endpoint, configured from these
+    /// options.
+    fn local_api(&self, base_url: &str) -> Result<AntithesisApi> {
+        let timeouts = Timeouts {
+            connect: self.connect_timeout,
+            ..Timeouts::default()
+        };
+        Ok(
+            AntithesisApi::unauthenticated(base_url, timeouts, self.max_redirects)?
+                .with_signer(self.signer()),
+        )
+    }
+
+    /// Print the settings a launch would use and where each came from, for
+    /// --print-config. `local` is the --local URL, if any, and `matches` are
+    /// the subcommand's parsed args.
+    fn print_config(
+        &self,
+        env_defaults: &EnvDefaults,
+        profile: Option<&str>,
+        local: Option<&str>,
+        matches: &ArgMatches,
+    ) {
+        let mut rows = match local {
+            Some(url) => vec![
+                ("username", "(none)".to_string(), "--local".to_string()),
+                ("base_url", url.to_string(), "--local".to_string()),
+            ],
+            None => api::describe_env(env_defaults, profile),
+        };
+        let duration = |d: std::time::Duration| humantime::format_duration(d).to_string();
+        let source = |id: &str, env: &str| option_source(matches, id, env);
+        let retry_on = self.retry_on.iter().map(u16::to_string).collect::<Vec<_>>();
+        let retry_on = if retry_on.is_empty() {
+            "(none)".to_string()
+        } else {
+            retry_on.join(",")
+        };
+        let jitter = self
+            .retry_jitter
+            .to_possible_value()
+            .expect("no skipped variants");
+
+        rows.extend([
+            // Not configurable
+            (
+                "timeout",
+                duration(Timeouts::default().total),
+                "fixed".to_string(),
+            ),
+            (
+                "connect_timeout",
+                duration(self.connect_timeout),
+                source("connect_timeout", "ANTITHESIS_CONNECT_TIMEOUT"),
+            ),
+            ("retries", self.retries.to_string(), source("retries", "")),
+            ("retry_on", retry_on, source("retry_on", "")),
+            (
+                "retry_base_delay",
+                duration(self.retry_base_delay),
+                source("retry_base_delay", "ANTITHESIS_RETRY_BASE_DELAY"),
+            ),
+            (
+                "retry_max_delay",
+                duration(self.retry_max_delay),
+                source("retry_max_delay", "ANTITHESIS_RETRY_MAX_DELAY"),
+            ),
+            (
+                "retry_jitter",
+                jitter.get_name().to_string(),
+                source("retry_jitter", "ANTITHESIS_RETRY_JITTER"),
+            ),
+        ]);
+
+        let width = rows
+            .iter()
+            .map(|(name, _, _)| name.len())
+            .max()
+            .unwrap_or(0)
+            + 1;
+        for (name, value, source) in rows {
+            println!("{:width$} {} ({})", format!("{}:", name), value, source);
+        }
+    }
+
+    /// A signer for launch bodies, if ANTITHESIS_SIGNING_KEY is set.
+    fn signer(&self) -> Option<Signer> {
+        Signer::from_env(self.signature_header.clone())
+    }
+
+    /// The params to show in the preview: redacted, and annotated with their
+    /// sources with --show-source.
+    fn preview(&self, params: &Params) -> Map<String, Value> {
+        self.ordered(if self.show_source {
+            params.to_sourced_map()
+        } else {
+            params.to_redacted_map()
+        })
+    }
+
+    /// The part of a response body to inspect and print: the
+    /// --response-root field as pretty JSON, or the whole body.
+    fn response_root(&self, body: String) -> Result<String> {
+        match &self.response_root {
+            None => Ok(body),
+            Some(root) => Ok(serde_json::to_string_pretty(&root.select(&body)?).unwrap()),
+        }
+    }
+
+    /// With --exec, run the command with the response body on its stdin and
+    /// wait for it to finish.
+    fn exec(&self, body: &str) -> Result<()> {
+        let Some(command) = &self.exec else {
+            return Ok(());
+        };
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let spawn_error = |e: io::Error| {
+            Error::InvalidArgs(format!("failed to run --exec command {:?}: {}", command, e))
+        };
+        let mut child = Command::new(shell)
+            .args([flag, command])
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(spawn_error)?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A command that exits without reading all of its input isn't an
+            // error here; its exit status decides
+            let _ = stdin.write_all(body.as_bytes());
+        }
+        let status = child.wait().map_err(spawn_error)?;
+        if !status.success() {
+            return Err(Error::ExecFailed {
+                command: command.clone(),
+                status,
+            });
+        }
+        Ok(())
+    }
+
+    /// Print the `--print-field` field of the response, if one was given.
+    fn print_field(&self, body: &str) -> Result<()> {
+        if let Some(pointer) = &self.print_field {
+            println!("{}", pointer.extract(body)?);
+        }
+        Ok(())
+    }
+
+    /// Sleep for the `--after` delay, if one was given.
+    async fn wait_before_launch(&self) {
+        if let Some(delay) = self.after {
+            eprintln!(
+                "\nWaiting {} before launching",
+                humantime::format_duration(delay)
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Where the option `id` got its value, for --print-config: its flag, its
+/// environment variable `env`, or the default.
+fn option_source(matches: &ArgMatches, id: &str, env: &str) -> String {
+    match matches.value_source(id) {
+        Some(ValueSource::CommandLine) => format!("--{}", id.replace('_', "-")),
+        Some(ValueSource::EnvVariable) => format!("env {}", env),
+        _ => "default".to_string(),
+    }
+}
+
 fn read_stdin() -> Result<String> {
     let mut buf = String::new();
     io::stdin()
         .read_to_string(&mut buf)
-        .map_err(|e| error::Error::InvalidArgs(format!("failed to read stdin: {}", e)))?;
+        .map_err(|e| Error::InvalidArgs(format!("failed to read stdin: {}", e)))?;
     let buf = buf.trim().to_string();
     Ok(buf)
 }
 
-fn get_params(args: Vec<String>, use_stdin: bool, support_moment: bool) -> Result<Params> {
-    // Parse stdin params if --stdin flag is set
-    let stdin_params = if use_stdin {
-        let input = read_stdin()?;
-        if support_moment && moment::is_moment_format(&input) {
-            debug!("detected Moment.from on stdin");
-            Some(moment::parse(&input)?)
-        } else {
-            debug!("parsing input as JSON");
-            let value: serde_json::Value = json5::from_str(&input)
-                .map_err(|e| error::Error::InvalidArgs(format!("invalid JSON: {}", e)))?;
-            Some(Params::from_json(&value)?)
+/// Read everything from an inherited file descriptor.
+#[cfg(unix)]
+fn read_fd(fd: u32) -> Result<String> {
+    // Reopening via /dev/fd avoids taking ownership of a raw descriptor
+    let contents = fs::read_to_string(format!("/dev/fd/{}", fd))
+        .map_err(|e| Error::InvalidArgs(format!("failed to read fd {}: {}", fd, e)))?;
+    Ok(contents.trim().to_string())
+}
+
+#[cfg(not(unix))]
+fn read_fd(_fd: u32) -> Result<String> {
+    Err(Error::InvalidArgs(
+        "--params-fd is only supported on Unix".to_string(),
+    ))
+}
+
+/// Parse params read from stdin or a file descriptor, returning them and
+/// whether they were a Moment.from.
+fn parse_input(input: &str, format: ParamsFormat, support_moment: bool) -> Result<(Params, bool)> {
+    let is_moment = match format {
+        ParamsFormat::Auto => support_moment && moment::is_moment_format(input),
+        ParamsFormat::Json => false,
+        ParamsFormat::Moment => true,
+        ParamsFormat::Toml => {
+            debug!("parsing input as TOML");
+            return Ok((Params::from_toml(input)?, false));
         }
+    };
+    if is_moment {
+        debug!("parsing input as Moment.from");
+        Ok((moment::parse(input)?, true))
     } else {
-        None
+        debug!("parsing input as JSON");
+        Ok((Params::from_json5(input)?, false))
+    }
+}
+
+/// Read a params file in `format`.
+///
+/// With [`ParamFileFormat::Auto`], the format comes from the file's
+/// extension. A file with any other extension, such as `.params`, is tried
+/// as JSON, then TOML, then YAML, and the first that parses wins.
+fn read_params_file(path: &Path, format: ParamFileFormat) -> Result<Params> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Error::InvalidArgs(format!("failed to read {}: {}", path.display(), e)))?;
+    let in_file = |e| match e {
+        Error::InvalidArgs(message) => {
+            Error::InvalidArgs(format!("{}: {}", path.display(), message))
+        }
+        other => other,
+    };
+
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    let format = match (format, extension) {
+        (ParamFileFormat::Auto, Some("json" | "json5")) => ParamFileFormat::Json,
+        (ParamFileFormat::Auto, Some("toml")) => ParamFileFormat::Toml,
+        (ParamFileFormat::Auto, Some("yaml" | "yml")) => ParamFileFormat::Yaml,
+        (ParamFileFormat::Auto, _) => {
+            let mut first_error = None;
+            for format in [
+                ParamFileFormat::Json,
+                ParamFileFormat::Toml,
+                ParamFileFormat::Yaml,
+            ] {
+                match format.parse(&contents) {
+                    Ok(params) => {
+                        debug!("parsed {} as {}", path.display(), format.name());
+                        return Ok(params);
+                    }
+                    Err(e) => {
+                        debug!("{} is not {}: {}", path.display(), format.name(), e);
+                        first_error.get_or_insert(e);
+                    }
+                }
+            }
+            // Report the JSON error, the likeliest intent; the others are
+            // in the debug log
+            return Err(in_file(first_error.expect("tried at least one format")));
+        }
+        (format, _) => format,
     };
+    debug!("parsing {} as {}", path.display(), format.name());
+    format.parse(&contents).map_err(in_file)
+}
+
+/// Read a complete launch body from a file.
+///
+/// The body is sent as-is; the params used for the preview and ETA are taken
+/// from its `params` object when present, or from the top level otherwise.
+fn read_body_file(path: &Path) -> Result<(Params, String)> {
+    let body = fs::read_to_string(path)
+        .map_err(|e| Error::InvalidArgs(format!("failed to read {}: {}", path.display(), e)))?;
+    let body = body.trim().to_string();
+    let value: Value = serde_json::from_str(&body)
+        .map_err(|e| Error::InvalidArgs(format!("invalid JSON in {}: {}", path.display(), e)))?;
+    if !value.is_object() {
+        return Err(Error::InvalidArgs(format!(
+            "expected a JSON object in {}",
+            path.display()
+        )));
+    }
+    let params = match value.get("params") {
+        Some(params) if params.is_object() => Params::from_json(params)?,
+        _ => Params::from_json(&value)?,
+    };
+    Ok((params, body))
+}
+
+fn get_params(
+    args: Vec<String>,
+    use_stdin: bool,
+    support_moment: bool,
+    file_params: Option<Params>,
+    launch: &LaunchOpts,
+) -> Result<Params> {
+    if launch.no_merge && use_stdin && !args.is_empty() {
+        return Err(Error::InvalidArgs(
+            "--no-merge takes params from either --stdin or the command line, not both".to_string(),
+        ));
+    }
+
+    // Params from --params-from-env have the lowest priority
+    let mut input_params = launch
+        .params_from_env
+        .as_deref()
+        .map(Params::from_env_prefix);
+
+    // Then params from a manifest or --watch-file
+    if let Some(params) = file_params {
+        match &mut input_params {
+            Some(merged) => merged.merge(params),
+            None => input_params = Some(params),
+        }
+    }
+
+    // Parse stdin params if --stdin flag is set
+    let mut from_moment = false;
+    if use_stdin {
+        if launch.params_format == ParamsFormat::Moment && !support_moment {
+            return Err(Error::InvalidArgs(
+                "--params-format moment is only supported by snouty debug".to_string(),
+            ));
+        }
+        let (params, is_moment) =
+            parse_input(&read_stdin()?, launch.params_format, support_moment)?;
+        from_moment |= is_moment;
+        let params = params.with_source(Source::Stdin);
+        match &mut input_params {
+            Some(merged) => merged.merge(params),
+            None => input_params = Some(params),
+        }
+    }
+
+    // Then any --params-fd sources, each overriding the ones before it
+    for &fd in &launch.params_fds {
+        let (params, is_moment) = parse_input(&read_fd(fd)?, ParamsFormat::Auto, support_moment)?;
+        from_moment |= is_moment;
+        let params = params.with_source(Source::File);
+        match &mut input_params {
+            Some(merged) => merged.merge(params),
+            None => input_params = Some(params),
+        }
+    }
 
     // Parse CLI args if provided
     let args_params = if !args.is_empty() {
-        Some(Params::from_args(&args)?)
+        Some(Params::from_args_with(&args, &launch.args_options())?.with_source(Source::Cli))
     } else {
         None
     };
 
-    // Merge params: CLI args take priority over stdin
-    match (stdin_params, args_params) {
-        (Some(mut stdin), Some(args)) => {
-            stdin.merge(args);
-            Ok(stdin)
+    // Merge params: CLI args take priority over stdin and fds
+    let params = match (input_params, args_params) {
+        (Some(mut input), Some(args)) => {
+            input.merge(args);
+            input
+        }
+        (Some(input), None) => input,
+        (None, Some(args)) => args,
+        (None, None) => return Err(Error::InvalidArgs("no parameters provided".to_string())),
+    };
+
+    if from_moment {
+        moment::check_complete(&params)?;
+    }
+    Ok(params)
+}
+
+/// Parse the command line.
+///
+/// Params normally start at the first argument snouty doesn't recognize, so
+/// snouty's own flags must come first. A standalone `--` makes the boundary
+/// explicit: everything before it must be a snouty flag, and everything after
+/// it is params.
+///
+/// The subcommand's raw matches are returned too, for --print-config to tell
+/// where each option's value came from.
+fn parse_cli() -> (Cli, ArgMatches) {
+    let argv: Vec<OsString> = std::env::args_os().collect();
+    if let Some(split) = argv.iter().position(|arg| arg == "--") {
+        let before = Cli::parse_from(&argv[..split]);
+        let params: &[String] = match &before.command {
+            Commands::Run(run) => &run.args,
+            Commands::Debug(debug) => &debug.args,
+            Commands::Apply(apply) => &apply.run.args,
+            Commands::Diff(_) | Commands::Version | Commands::Update => &[],
+        };
+        if let Some(arg) = params.first() {
+            Cli::command()
+                .error(
+                    clap::error::ErrorKind::UnknownArgument,
+                    format!(
+                        "unexpected argument {:?} before --; params go after --",
+                        arg
+                    ),
+                )
+                .exit();
         }
-        (Some(stdin), None) => Ok(stdin),
-        (None, Some(args)) => Ok(args),
-        (None, None) => Err(Error::InvalidArgs("no parameters provided".to_string())),
     }
+    // clap itself treats everything after `--` as the trailing params
+    let matches = Cli::command().get_matches_from(argv);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let matches = matches
+        .subcommand()
+        .map(|(_, matches)| matches.clone())
+        .unwrap_or_default();
+    (cli, matches)
 }
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
-    env_logger::init();
-    let cli = Cli::parse();
+    let (cli, matches) = parse_cli();
+    logging::init(cli.log_format);
+    warnings::set_strict(cli.strict_warnings);
+    #[cfg(feature = "otel")]
+    let tracer_provider = otel::init();
+
+    // Catch edits that break the bundled schema before they reach a release,
+    // rather than at the first validation
+    #[cfg(debug_assertions)]
+    if let Err(e) =
+        Schema::bundled().and_then(|schema| schema.check().map_err(Error::InvalidSchema))
+    {
+        panic!("bundled params_schema.json is invalid: {}", e);
+    }
 
     let result = match cli.command {
-        Commands::Run {
-            webhook,
-            stdin,
-            args,
-        } => {
-            info!("running test with webhook: {}", webhook);
-            cmd_run(webhook, args, stdin).await
-        }
-        Commands::Debug { stdin, args } => {
-            info!("starting debug session");
-            cmd_debug(args, stdin).await
+        Commands::Run(run) => {
+            if run.launch.list_params {
+                cmd_list_params("testParams")
+            } else if run.launch.print_config {
+                cmd_print_config(
+                    &run.launch,
+                    run.local.as_deref(),
+                    &matches,
+                    cli.profile.as_deref(),
+                )
+            } else if run.watch_file.is_some() {
+                cmd_watch(run, cli.profile.as_deref()).await
+            } else {
+                cmd_run(run, None, cli.profile.as_deref()).await
+            }
+        }
+        Commands::Debug(debug) => {
+            if debug.launch.list_params {
+                cmd_list_params("debuggingParams")
+            } else if debug.launch.print_config {
+                cmd_print_config(&debug.launch, None, &matches, cli.profile.as_deref())
+            } else {
+                info!("starting debug session");
+                cmd_debug(debug, cli.profile.as_deref()).await
+            }
         }
         Commands::Version => {
             println!("snouty {}", env!("CARGO_PKG_VERSION"));
-            Ok(())
+            Schema::bundled().map(|schema| {
+                if let Some(version) = schema.version() {
+                    println!("schema_version: {}", version);
+                }
+            })
         }
+        Commands::Apply(apply) if apply.run.launch.print_config => cmd_print_config(
+            &apply.run.launch,
+            apply.run.local.as_deref(),
+            &matches,
+            cli.profile.as_deref(),
+        ),
+        Commands::Apply(apply) => cmd_apply(apply, cli.profile.as_deref()).await,
+        Commands::Diff(diff) => cmd_diff(&diff),
         Commands::Update => cmd_update(),
     };
 
+    #[cfg(feature = "otel")]
+    if let Some(provider) = tracer_provider
+        && let Err(e) = provider.shutdown()
+    {
+        log::warn!("failed to flush OpenTelemetry spans: {}", e);
+    }
+
     if let Err(e) = result {
         eprintln!("error: {}", e);
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
 
-async fn cmd_run(webhook: String, args: Vec<String>, use_stdin: bool) -> Result<()> {
-    let params = get_params(args, use_stdin, false)?;
-    params.validate_test_params()?;
+/// Send a launch request, returning the response status and body if the
+/// launch succeeded. With the `otel` feature, the launch is also recorded as
+/// a span.
+async fn send_launch(
+    api: &AntithesisApi,
+    webhook: &str,
+    request: RequestBuilder,
+    retry_policy: &RetryPolicy,
+    expect_field: Option<&ExpectField>,
+    fail_on_empty: bool,
+    trace: bool,
+) -> Result<(StatusCode, String)> {
+    debug!("launching {}", webhook);
+    #[cfg(feature = "otel")]
+    let start = std::time::SystemTime::now();
+    let result = send_launch_request(
+        api,
+        request,
+        retry_policy,
+        expect_field,
+        fail_on_empty,
+        trace,
+    )
+    .await;
+    #[cfg(feature = "otel")]
+    otel::record_launch(webhook, launch_status(&result), result.is_ok(), start);
+    result
+}
 
-    // Print params to stderr for user visibility (with sensitive values redacted)
-    eprintln!(
-        "\nRequesting Antithesis test run with params:\n{}",
-        serde_json::to_string_pretty(&params.to_redacted_map()).unwrap()
-    );
+/// The HTTP status a launch ended with, if it got a response.
+fn launch_status(result: &Result<(StatusCode, String)>) -> Option<u16> {
+    match result {
+        Ok((status, _)) => Some(status.as_u16()),
+        Err(Error::Api { status, .. }) => Some(*status),
+        Err(_) => None,
+    }
+}
 
-    let api = AntithesisApi::from_env()?;
-    let response = api
-        .post(&format!("/launch/{}", webhook))
-        .json(&serde_json::json!({ "params": params.to_value() }))
-        .send()
-        .await?;
+async fn send_launch_request(
+    api: &AntithesisApi,
+    request: RequestBuilder,
+    retry_policy: &RetryPolicy,
+    expect_field: Option<&ExpectField>,
+    fail_on_empty: bool,
+    trace: bool,
+) -> Result<(StatusCode, String)> {
+    if trace && let Some(Ok(built)) = request.try_clone().map(RequestBuilder::build) {
+        eprint!("{}", api::trace_request(&built));
+    }
+
+    let (response, attempts) = api.send_with_retry(request, retry_policy).await?;
+    info!("response received, attempts: {}", attempts);
 
     let status = response.status();
+    let headers = response.headers().clone();
+    let response_path = response.url().path().to_string();
+    // Checked after the status, so an unreadable error body still reports
+    // the API error
+    let encoding = api::check_content_encoding(&response);
     let body = response.text().await?;
     debug!("response status: {}, body:\n{}", status, body);
+    if trace {
+        eprint!("{}", api::trace_response(status, &headers, &body));
+    }
 
-    if status.is_success() {
-        // Estimate when the report email will arrive
-        let duration_mins: i64 = params
-            .as_map()
-            .get("antithesis.duration")
-            .and_then(|v| v.as_str())
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0);
-        let eta = Local::now() + Duration::minutes(duration_mins + 10);
-        eprintln!(
-            "\nExpect a report email from Antithesis around {}",
-            eta.format("%b %-d at %-I:%M %p")
-        );
-
-        Ok(())
-    } else {
-        Err(error::Error::Api {
+    if !status.is_success() {
+        return Err(Error::Api {
             status: status.as_u16(),
+            // Launches are always POSTs
+            method: Method::POST,
+            path: response_path,
             message: body,
-        })
+        });
+    }
+    encoding?;
+    if fail_on_empty && body.trim().is_empty() {
+        return Err(Error::UnexpectedResponse(format!(
+            "{} with an empty body; check the webhook name and launch path",
+            status
+        )));
     }
+    if let Some(expect) = expect_field {
+        expect.check(&body)?;
+    }
+    Ok((status, body))
+}
+
+/// Launch the same request `repeat` times, at most `concurrency` at once, and
+/// print a summary of the outcomes.
+///
+/// Each launch carries its own `Idempotency-Key` so the server doesn't
+/// deduplicate them.
+async fn send_repeated(
+    api: &AntithesisApi,
+    webhook: &str,
+    fingerprint: &str,
+    path: &str,
+    body: &str,
+    opts: &RepeatOpts,
+    launch: &LaunchOpts,
+) -> Result<()> {
+    let RepeatOpts {
+        repeat,
+        concurrency,
+        count_only,
+        verbose,
+        batch_retry_budget,
+        output,
+    } = *opts;
+    eprintln!("\nLaunching {} runs, {} at a time", repeat, concurrency);
+
+    let mut retry_policy = launch.retry_policy();
+    retry_policy.budget = batch_retry_budget.map(RetryBudget::new);
+
+    let semaphore = Arc::new(Semaphore::new(concurrency as usize));
+    let mut tasks = JoinSet::new();
+    for index in 1..=repeat {
+        let request = api.post_launch(path, body.to_string());
+        let api = api.clone();
+        let webhook = webhook.to_string();
+        let retry_policy = retry_policy.clone();
+        let expect_field = launch.expect_field.clone();
+        let fail_on_empty = launch.fail_on_empty_response;
+        let trace_request = launch.trace_request;
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let _permit = semaphore
+                .acquire_owned()
+                .await
+                .expect("semaphore is never closed");
+            let result = send_launch(
+                &api,
+                &webhook,
+                request,
+                &retry_policy,
+                expect_field.as_ref(),
+                fail_on_empty,
+                trace_request,
+            )
+            .await;
+            (index, result)
+        });
+    }
+
+    let mut outcomes = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (index, result) = joined.expect("launch task panicked");
+        if output == BatchOutput::Ndjson {
+            let status = launch_status(&result);
+            let error = result.as_ref().err().map(ToString::to_string);
+            // Keys in sorted order, like the rest of snouty's JSON output
+            let line = serde_json::json!({
+                "error": error,
+                "index": index,
+                "params_fingerprint": fingerprint,
+                "status": status,
+                "webhook": webhook,
+            });
+            println!("{}", line);
+        }
+        outcomes.push((index, result));
+    }
+    outcomes.sort_by_key(|(index, _)| *index);
+
+    let launched = outcomes.iter().filter(|(_, result)| result.is_ok()).count() as u32;
+    if verbose {
+        for (index, result) in &outcomes {
+            match result {
+                Ok(_) => eprintln!("Run {}/{}: launched", index, repeat),
+                Err(e) => eprintln!("Run {}/{}: failed: {}", index, repeat, e),
+            }
+        }
+    }
+    if !count_only {
+        eprintln!("Launched {} of {} runs", launched, repeat);
+    }
+    // A machine-readable summary, e.g. for CI step outputs. With ndjson,
+    // stdout holds only the JSON lines.
+    if output == BatchOutput::Text {
+        println!("launched={} failed={}", launched, repeat - launched);
+    }
+
+    if launched < repeat {
+        return Err(Error::LaunchesFailed {
+            failed: (repeat - launched) as usize,
+            total: repeat as usize,
+        });
+    }
+    Ok(())
+}
+
+/// Remove a `webhook` or `antithesis.webhook` key from the params, returning
+/// its value. It picks the webhook to call and isn't sent as a param.
+fn take_webhook(params: &mut Params) -> Result<Option<String>> {
+    let mut webhook = None;
+    for key in ["webhook", "antithesis.webhook"] {
+        match params.remove(key) {
+            Some(Value::String(value)) => {
+                webhook.get_or_insert(value);
+            }
+            Some(other) => {
+                return Err(Error::InvalidArgs(format!(
+                    "{} must be a string, got {}",
+                    key, other
+                )));
+            }
+            None => {}
+        }
+    }
+    Ok(webhook)
+}
+
+/// Launch a manifest's run, with the command line's options and params taking
+/// priority over the manifest's.
+async fn cmd_apply(apply: ApplyArgs, profile: Option<&str>) -> Result<()> {
+    let manifest = ApplyManifest::from_file(&apply.manifest)?;
+    let mut run = apply.run;
+    run.launch.after = run.launch.after.or(manifest.after());
+    let params = manifest.params();
+    run.webhook.get_or_insert(manifest.webhook);
+    cmd_run(run, Some(params), profile).await
 }
 
-async fn cmd_debug(args: Vec<String>, use_stdin: bool) -> Result<()> {
-    let params = get_params(args, use_stdin, true)?;
-    params.validate_debugging_params()?;
+/// Launch with the params in `--watch-file`, then relaunch each time the file
+/// changes. A failed launch is reported and watching carries on; Ctrl-C stops.
+async fn cmd_watch(run: RunArgs, profile: Option<&str>) -> Result<()> {
+    let path = run.watch_file.clone().expect("checked by caller");
+    let mut watcher = FileWatcher::new(&path)?;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+
+    loop {
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            result = launch_watched(&run, &path, profile) => {
+                if let Err(e) = result {
+                    eprintln!("error: {}", e);
+                }
+                eprintln!("Watching {} for changes (Ctrl-C to stop)", path.display());
+            }
+        }
+        tokio::select! {
+            _ = &mut ctrl_c => break,
+            () = watcher.changed() => eprintln!("{} changed, relaunching", path.display()),
+        }
+    }
+    eprintln!("Stopped watching {}", path.display());
+    Ok(())
+}
+
+/// One launch for `--watch-file`, with the file's params under the command
+/// line's so that explicit args still win.
+async fn launch_watched(run: &RunArgs, path: &Path, profile: Option<&str>) -> Result<()> {
+    let params = read_params_file(path, ParamFileFormat::Auto)?.with_source(Source::File);
+    let mut run = run.clone();
+    run.watch_file = None;
+    cmd_run(run, Some(params), profile).await
+}
+
+/// Launch a test run. `file_params` come from a manifest or --watch-file; they
+/// override params from the environment, and the other sources override them.
+async fn cmd_run(run: RunArgs, file_params: Option<Params>, profile: Option<&str>) -> Result<()> {
+    let launch = &run.launch;
+    launch.check_template()?;
+    if run.batch.repeat > 1 {
+        let single = [
+            ("--template", launch.template.is_some()),
+            ("--print-field", launch.print_field.is_some()),
+            ("--exec", launch.exec.is_some()),
+        ];
+        if let Some((flag, _)) = single.iter().find(|(_, set)| *set) {
+            return Err(Error::InvalidArgs(format!(
+                "{} can't be combined with --repeat",
+                flag
+            )));
+        }
+    }
+    let settings = Settings::load()?;
+    let env_defaults = settings.env_defaults(profile)?;
+
+    let (params, body, params_webhook) = match run.body_file {
+        // A pre-built body bypasses params parsing and schema validation
+        Some(path) => {
+            let (params, body) = read_body_file(&path)?;
+            (params.with_source(Source::File), body, None)
+        }
+        None => {
+            // Params from the environment and --images-from have the lowest priority
+            let mut params = settings.env_params();
+            if let Some(path) = &run.images_from {
+                params.insert_from(
+                    "antithesis.images",
+                    manifest::images_from_file(path)?.join(";"),
+                    Source::File,
+                );
+            }
+            params.merge(get_params(run.args, run.stdin, false, file_params, launch)?);
+            let params_webhook = take_webhook(&mut params)?;
+            for label in run.labels {
+                params.insert_from(label.param_key(), label.value, Source::Cli);
+            }
+            for var in run.env {
+                params.insert_from(var.param_key(), var.value, Source::Cli);
+            }
+            if run.humanize_duration {
+                params.humanize_duration()?;
+            }
+            launch.report_validation(params.validate_test_params_with(
+                &launch.load_schema(&settings, &env_defaults).await?,
+                &launch.validate_options(),
+            ))?;
+            params.normalize_duration()?;
+            let body = launch.request_body(&params);
+            (params, body, params_webhook)
+        }
+    };
+    launch.check_required_fields(&params, &settings)?;
+
+    let webhook = match (run.webhook, params_webhook, &settings.default_webhook) {
+        (Some(webhook), Some(from_params), _) if webhook != from_params => {
+            warnings::warn(format_args!(
+                "--webhook {} overrides the webhook {} in the params",
+                webhook, from_params
+            ))?;
+            webhook
+        }
+        (Some(webhook), _, _) => webhook,
+        (None, Some(webhook), _) => {
+            info!("using webhook from params: {}", webhook);
+            webhook
+        }
+        (None, None, Some(default)) => {
+            info!("using default_webhook from config: {}", default);
+            default.clone()
+        }
+        (None, None, None) => {
+            return Err(Error::InvalidArgs(
+                "missing --webhook (or set default_webhook in the config file)".to_string(),
+            ));
+        }
+    };
+    info!("running test with webhook: {}", webhook);
+
+    if launch.only_validate {
+        eprintln!("Params are valid");
+        return Ok(());
+    }
+
+    if launch.export_params(&params)? {
+        return Ok(());
+    }
+
+    check_body_size(&body)?;
+    launch.check_no_leak(&params)?;
 
     // Print params to stderr for user visibility (with sensitive values redacted)
+    if !launch.no_preview {
+        eprintln!(
+            "\nRequesting Antithesis test run with params:\n{}",
+            serde_json::to_string_pretty(&launch.preview(&params)).unwrap()
+        );
+        eprintln!("Params fingerprint: {}", params.fingerprint());
+    }
+
+    launch.preflight(&params).await?;
+
+    let api = match &run.local {
+        Some(url) => launch.local_api(url)?,
+        None => launch.api(&env_defaults)?,
+    };
+    let path = with_query(&settings.launch_path(&webhook), &launch.query);
+
+    if launch.print_curl {
+        println!("{}", api.curl_command(&path, &body));
+        return Ok(());
+    }
+
+    launch.check_protected_tenant(&api, &settings)?;
+    launch.wait_before_launch().await;
+
+    let mut response = None;
+    if run.batch.repeat > 1 {
+        let fingerprint = params.fingerprint();
+        send_repeated(
+            &api,
+            &webhook,
+            &fingerprint,
+            &path,
+            &body,
+            &run.batch,
+            launch,
+        )
+        .await?;
+    } else {
+        let request = api.post_launch(&path, body);
+        let (status, body) = send_launch(
+            &api,
+            &webhook,
+            request,
+            &launch.retry_policy(),
+            launch.expect_field.as_ref(),
+            launch.fail_on_empty_response,
+            launch.trace_request,
+        )
+        .await?;
+        launch.write_response(&body)?;
+        launch.exec(&body)?;
+        let body = launch.response_root(body)?;
+        launch.print_field(&body)?;
+        response = Some((status, body));
+    }
+
+    // Estimate when the report email will arrive
+    let duration_mins: i64 = params
+        .as_map()
+        .get("antithesis.duration")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0);
+    let buffer_mins = run
+        .email_buffer_mins
+        .or(settings.email_buffer_mins)
+        .unwrap_or(DEFAULT_EMAIL_BUFFER_MINS);
+    let eta = Local::now() + Duration::minutes(duration_mins + i64::from(buffer_mins));
     eprintln!(
-        "\nRequesting the Antithesis multiverse debugger with params:\n{}",
-        serde_json::to_string_pretty(&params.to_redacted_map()).unwrap()
+        "\nExpect a report email from Antithesis around {}",
+        eta.format("%b %-d at %-I:%M %p")
     );
+    if let Some((status, body)) = response {
+        launch.print_template(&webhook, &eta, status, &body)?;
+    }
 
-    let api = AntithesisApi::from_env()?;
-    let response = api
-        .post("/launch/debugging")
-        .json(&serde_json::json!({ "params": params.to_value() }))
-        .send()
-        .await?;
+    Ok(())
+}
 
-    let status = response.status();
-    let body = response.text().await?;
-    debug!("response status: {}, body length: {}", status, body.len());
+async fn cmd_debug(debug: DebugArgs, profile: Option<&str>) -> Result<()> {
+    let launch = &debug.launch;
+    launch.check_template()?;
+    let settings = Settings::load()?;
+    let env_defaults = settings.env_defaults(profile)?;
+    let params = match &debug.report_url {
+        Some(url) => {
+            let report = report::debugging_params(&launch.api(&env_defaults)?, url).await?;
+            let mut params = Params::from(report).with_source(Source::Report);
+            if !debug.args.is_empty() {
+                params.merge(
+                    Params::from_args_with(&debug.args, &launch.args_options())?
+                        .with_source(Source::Cli),
+                );
+            }
+            params
+        }
+        None => get_params(debug.args, debug.stdin, true, None, launch)?,
+    };
+    launch.check_required_fields(&params, &settings)?;
+    launch.report_validation(params.validate_debugging_params_with(
+        &launch.load_schema(&settings, &env_defaults).await?,
+        &launch.validate_options(),
+    ))?;
+
+    if launch.only_validate {
+        eprintln!("Params are valid");
+        return Ok(());
+    }
+
+    if launch.export_params(&params)? {
+        return Ok(());
+    }
 
-    if status.is_success() {
-        println!("{}", body);
+    launch.check_no_leak(&params)?;
 
-        // Estimate when the debugging session email will arrive
-        let eta = Local::now() + Duration::minutes(10);
+    // Print params to stderr for user visibility (with sensitive values redacted)
+    if !launch.no_preview {
         eprintln!(
-            "\nExpect a debugging session email from Antithesis around {}",
-            eta.format("%b %-d at %-I:%M %p")
+            "\nRequesting the Antithesis multiverse debugger with params:\n{}",
+            serde_json::to_string_pretty(&launch.preview(&params)).unwrap()
         );
+        eprintln!("Params fingerprint: {}", params.fingerprint());
+    }
 
-        Ok(())
-    } else {
-        Err(error::Error::Api {
-            status: status.as_u16(),
-            message: body,
-        })
+    launch.preflight(&params).await?;
+
+    let api = launch.api(&env_defaults)?;
+    let path = with_query(&settings.launch_path("debugging"), &launch.query);
+    let body = launch.request_body(&params);
+    check_body_size(&body)?;
+
+    if launch.print_curl {
+        println!("{}", api.curl_command(&path, &body));
+        return Ok(());
     }
+
+    launch.check_protected_tenant(&api, &settings)?;
+    launch.wait_before_launch().await;
+
+    let request = api.post_launch(&path, body);
+    let (status, body) = send_launch(
+        &api,
+        "debugging",
+        request,
+        &launch.retry_policy(),
+        launch.expect_field.as_ref(),
+        launch.fail_on_empty_response,
+        launch.trace_request,
+    )
+    .await?;
+
+    launch.write_response(&body)?;
+    launch.exec(&body)?;
+    let body = launch.response_root(body)?;
+    if launch.response_out.is_none() {
+        if launch.print_field.is_some() {
+            // stdout is reserved for the field
+            eprintln!("{}", body);
+        } else {
+            println!("{}", body);
+        }
+    }
+    launch.print_field(&body)?;
+
+    if debug.print_moment {
+        eprintln!("\nDebugging moment:\n{}", moment::format(&params));
+    }
+
+    // Estimate when the debugging session email will arrive
+    let eta = Local::now() + Duration::minutes(10);
+    eprintln!(
+        "\nExpect a debugging session email from Antithesis around {}",
+        eta.format("%b %-d at %-I:%M %p")
+    );
+    launch.print_template("debugging", &eta, status, &body)?;
+
+    Ok(())
+}
+
+fn cmd_print_config(
+    launch: &LaunchOpts,
+    local: Option<&str>,
+    matches: &ArgMatches,
+    profile: Option<&str>,
+) -> Result<()> {
+    let env_defaults = Settings::load()?.env_defaults(profile)?;
+    launch.print_config(&env_defaults, profile, local, matches);
+    Ok(())
+}
+
+fn cmd_list_params(def_name: &str) -> Result<()> {
+    for (key, description) in params::describe_def(def_name)? {
+        println!("--{}", key);
+        if !description.is_empty() {
+            println!("    {}", description);
+        }
+    }
+    Ok(())
+}
+
+fn cmd_diff(diff: &DiffArgs) -> Result<()> {
+    let format = diff.param_file_format;
+    let changes = read_params_file(&diff.a, format)?.diff(&read_params_file(&diff.b, format)?);
+    if changes.is_empty() {
+        eprintln!("No differences");
+    }
+    for change in changes {
+        println!("{}", change);
+    }
+    Ok(())
 }
 
 fn cmd_update() -> Result<()> {
@@ -0,0 +1,134 @@
+//! Image references from docker-compose and Kubernetes manifests.
+
+use std::fs;
+use std::path::Path;
+
+use log::debug;
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::error::{Error, Result};
+
+/// Read the images referenced by a docker-compose or Kubernetes YAML file.
+pub fn images_from_file(path: &Path) -> Result<Vec<String>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| Error::InvalidArgs(format!("failed to read {}: {}", path.display(), e)))?;
+    let images = images(&contents)
+        .map_err(|e| Error::InvalidArgs(format!("invalid manifest {}: {}", path.display(), e)))?;
+    if images.is_empty() {
+        return Err(Error::InvalidArgs(format!(
+            "no images found in {}",
+            path.display()
+        )));
+    }
+    debug!("found {} images in {}", images.len(), path.display());
+    Ok(images)
+}
+
+/// Collect image references, in order and without duplicates.
+///
+/// Compose files contribute `services.*.image`. Kubernetes documents (a file
+/// may hold several, separated by `---`) contribute the `image` of every
+/// entry in a `containers` or `initContainers` list, wherever it's nested.
+pub fn images(contents: &str) -> std::result::Result<Vec<String>, serde_yaml::Error> {
+    let mut images = Vec::new();
+    for document in serde_yaml::Deserializer::from_str(contents) {
+        let value = Value::deserialize(document)?;
+        if let Some(services) = value.get("services").and_then(Value::as_mapping) {
+            for service in services.values() {
+                push_image(&mut images, service);
+            }
+        }
+        collect_container_images(&value, &mut images);
+    }
+    Ok(images)
+}
+
+fn collect_container_images(value: &Value, images: &mut Vec<String>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, value) in map {
+                if let (Some("containers" | "initContainers"), Some(containers)) =
+                    (key.as_str(), value.as_sequence())
+                {
+                    for container in containers {
+                        push_image(images, container);
+                    }
+                }
+                collect_container_images(value, images);
+            }
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                collect_container_images(item, images);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn push_image(images: &mut Vec<String>, value: &Value) {
+    if let Some(image) = value.get("image").and_then(Value::as_str)
+        && !images.iter().any(|i| i == image)
+    {
+        images.push(image.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_services() {
+        let compose = r#"
+services:
+  app:
+    image: registry.example.com/app:1.2
+    ports: ["8080:8080"]
+  db:
+    image: postgres:16
+  builder:
+    build: .
+"#;
+        assert_eq!(
+            images(compose).unwrap(),
+            ["registry.example.com/app:1.2", "postgres:16"]
+        );
+    }
+
+    #[test]
+    fn kubernetes_containers() {
+        let manifest = r#"
+apiVersion: apps/v1
+kind: Deployment
+spec:
+  template:
+    spec:
+      initContainers:
+        - name: migrate
+          image: app:1.2
+      containers:
+        - name: app
+          image: app:1.2
+        - name: sidecar
+          image: envoy:v1.30
+---
+apiVersion: v1
+kind: Pod
+spec:
+  containers:
+    - name: worker
+      image: worker@sha256:abc
+"#;
+        assert_eq!(
+            images(manifest).unwrap(),
+            ["app:1.2", "envoy:v1.30", "worker@sha256:abc"]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_yaml() {
+        assert!(images("services: [").is_err());
+    }
+}
@@ -1,7 +1,7 @@
 //! Parser for the Moment.from format used by Antithesis triage reports.
 
 use log::debug;
-use serde_json::{Map, Value};
+use serde_json::{Map, Number, Value};
 
 use crate::error::{Error, Result};
 use crate::params::Params;
@@ -10,7 +10,8 @@ use crate::params::Params;
 ///
 /// The format is: `Moment.from({ session_id: "...", input_hash: "...", vtime: 123.456 })`
 ///
-/// This is JSON5 object syntax with unquoted keys. The keys are converted
+/// This is JSON5 object syntax with unquoted keys; single-quoted strings, as
+/// some browser consoles render them, are accepted too. The keys are converted
 /// to `antithesis.debugging.*` format and numeric values are converted to strings.
 pub fn parse(input: &str) -> Result<Params> {
     let input = input.trim();
@@ -47,6 +48,56 @@ pub fn parse(input: &str) -> Result<Params> {
     Params::from_json(&Value::Object(map))
 }
 
+/// The Moment fields, in the order the triage report renders them.
+const MOMENT_FIELDS: [&str; 3] = ["session_id", "input_hash", "vtime"];
+
+/// Format debugging params as a canonical Moment.from string.
+///
+/// This is the inverse of [`parse`]. As in the triage report, `vtime` is
+/// written as a bare number and the other fields as quoted strings.
+pub fn format(params: &Params) -> String {
+    let fields: Vec<String> = MOMENT_FIELDS
+        .iter()
+        .filter_map(|field| {
+            let value = params
+                .as_map()
+                .get(&format!("antithesis.debugging.{}", field))?;
+            let rendered = match value {
+                Value::String(s) if *field == "vtime" && s.parse::<Number>().is_ok() => s.clone(),
+                other => other.to_string(),
+            };
+            Some(format!("{}: {}", field, rendered))
+        })
+        .collect();
+    format!("Moment.from({{ {} }})", fields.join(", "))
+}
+
+/// Check that params parsed from a Moment (possibly merged with CLI args) have
+/// every Moment field.
+///
+/// Some triage links omit fields such as `input_hash`; rather than failing
+/// schema validation, tell the user how to supply them on the command line.
+pub fn check_complete(params: &Params) -> Result<()> {
+    let missing: Vec<String> = MOMENT_FIELDS
+        .iter()
+        .map(|field| format!("antithesis.debugging.{}", field))
+        .filter(|key| !params.as_map().contains_key(key))
+        .collect();
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let flags: Vec<String> = missing
+        .iter()
+        .map(|key| format!("--{} <value>", key))
+        .collect();
+    Err(Error::InvalidArgs(format!(
+        "Moment.from is missing {}; supply it on the command line with {}",
+        missing.join(", "),
+        flags.join(" ")
+    )))
+}
+
 /// Check if input looks like a Moment.from format.
 pub fn is_moment_format(input: &str) -> bool {
     let input = input.trim();
@@ -70,6 +121,14 @@ mod tests {
         assert!(!is_moment_format("{ foo: 1 }"));
     }
 
+    #[test]
+    fn is_moment_format_ignores_moment_inside_json() {
+        assert!(!is_moment_format(
+            r#"{"antithesis.description": "repro of Moment.from({ vtime: 1 })"}"#
+        ));
+        assert!(!is_moment_format(r#"{"note": "Moment.from("}"#));
+    }
+
     #[test]
     fn parse_simple() {
         let input = r#"Moment.from({ session_id: "abc123" })"#;
@@ -103,6 +162,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_single_quoted_matches_double_quoted() {
+        let double = r#"Moment.from({ session_id: "abc-1", input_hash: "-42", vtime: 1.5 })"#;
+        let single = r#"Moment.from({ 'session_id': 'abc-1', input_hash: '-42', vtime: 1.5 })"#;
+
+        assert_eq!(
+            parse(single).unwrap().as_map(),
+            parse(double).unwrap().as_map()
+        );
+    }
+
     #[test]
     fn parse_converts_numbers_to_strings() {
         let input = r#"Moment.from({ count: 42, ratio: 3.14 })"#;
@@ -118,6 +188,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_round_trips_through_parse() {
+        let input = r#"Moment.from({ session_id: "f89d5c11f5e3bf5e4bb3641809800cee-44-22", input_hash: "6057726200491963783", vtime: 329.8037810830865 })"#;
+        let params = parse(input).unwrap();
+
+        let formatted = format(&params);
+
+        assert_eq!(formatted, input);
+        assert_eq!(parse(&formatted).unwrap().as_map(), params.as_map());
+    }
+
+    #[test]
+    fn partial_moment_reports_missing_fields() {
+        let mut params = parse(r#"Moment.from({ session_id: "abc", vtime: 329.8 })"#).unwrap();
+
+        let err = check_complete(&params).unwrap_err().to_string();
+        assert!(err.contains("missing antithesis.debugging.input_hash"));
+        assert!(err.contains("--antithesis.debugging.input_hash <value>"));
+
+        params.insert("antithesis.debugging.input_hash", "123");
+        assert!(check_complete(&params).is_ok());
+    }
+
     #[test]
     fn parse_rejects_invalid_format() {
         assert!(parse("{}").is_err());
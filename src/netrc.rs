@@ -0,0 +1,131 @@
+//! Credentials from a `.netrc` file.
+//!
+//! The file is read from `$NETRC`, or by default from `~/.netrc`. Only the
+//! `machine`, `default`, `login`, and `password` tokens are used; `macdef`
+//! bodies are skipped.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use log::debug;
+
+/// Look up the login and password for `host` in the user's `.netrc`.
+///
+/// Returns `None` if there is no `.netrc`, or it has no complete entry for
+/// `host` (or a `default` entry).
+pub fn credentials(host: &str) -> Option<(String, String)> {
+    let path = match env::var_os("NETRC") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(env::var_os("HOME")?).join(".netrc"),
+    };
+    let contents = fs::read_to_string(&path).ok()?;
+    let found = lookup(&contents, host);
+    if found.is_some() {
+        debug!("using credentials for {} from {}", host, path.display());
+    }
+    found
+}
+
+#[derive(Default)]
+struct Entry {
+    login: Option<String>,
+    password: Option<String>,
+}
+
+fn lookup(contents: &str, host: &str) -> Option<(String, String)> {
+    let mut matched: Option<Entry> = None;
+    let mut default: Option<Entry> = None;
+    // Which entry the current tokens belong to: the one for `host`, the
+    // default entry, or some other machine
+    let mut current: Option<&mut Entry> = None;
+    let mut in_macdef = false;
+
+    for line in contents.lines() {
+        if in_macdef {
+            // A macro definition ends at the first empty line
+            in_macdef = !line.trim().is_empty();
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "machine" => {
+                    current = if tokens.next() == Some(host) && matched.is_none() {
+                        Some(matched.insert(Entry::default()))
+                    } else {
+                        None
+                    };
+                }
+                "default" => current = Some(default.insert(Entry::default())),
+                "login" => {
+                    let value = tokens.next().map(str::to_string);
+                    if let Some(entry) = current.as_deref_mut() {
+                        entry.login = value;
+                    }
+                }
+                "password" => {
+                    let value = tokens.next().map(str::to_string);
+                    if let Some(entry) = current.as_deref_mut() {
+                        entry.password = value;
+                    }
+                }
+                "account" => {
+                    tokens.next();
+                }
+                "macdef" => {
+                    current = None;
+                    in_macdef = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let entry = matched.or(default)?;
+    Some((entry.login?, entry.password?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NETRC: &str = "\
+machine example.com login other password secret
+macdef init
+  machine acme.antithesis.com login wrong password wrong
+
+machine acme.antithesis.com
+  login alice
+  password hunter2
+";
+
+    #[test]
+    fn finds_matching_machine() {
+        assert_eq!(
+            lookup(NETRC, "acme.antithesis.com"),
+            Some(("alice".to_string(), "hunter2".to_string()))
+        );
+        assert_eq!(
+            lookup(NETRC, "example.com"),
+            Some(("other".to_string(), "secret".to_string()))
+        );
+        assert_eq!(lookup(NETRC, "unknown.antithesis.com"), None);
+    }
+
+    #[test]
+    fn falls_back_to_default() {
+        let netrc = "machine example.com login a password b\ndefault login c password d\n";
+        assert_eq!(
+            lookup(netrc, "acme.antithesis.com"),
+            Some(("c".to_string(), "d".to_string()))
+        );
+    }
+
+    #[test]
+    fn requires_login_and_password() {
+        assert_eq!(lookup("machine example.com login a", "example.com"), None);
+    }
+}
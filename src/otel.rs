@@ -0,0 +1,116 @@
+//! OpenTelemetry export of launches, with the `otel` feature.
+//!
+//! When `OTEL_EXPORTER_OTLP_ENDPOINT` is set, each launch is recorded as a
+//! `snouty.launch` span and exported over OTLP/HTTP. Spans carry the webhook,
+//! response status, and duration; never params or credentials.
+
+use std::env;
+use std::time::SystemTime;
+
+use log::{debug, warn};
+use opentelemetry::trace::{Span, Status, Tracer};
+use opentelemetry::{KeyValue, global};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+
+/// Install a global tracer provider exporting to `OTEL_EXPORTER_OTLP_ENDPOINT`,
+/// if it's set. Shut the provider down before exiting to flush its spans.
+pub fn init() -> Option<SdkTracerProvider> {
+    env::var_os("OTEL_EXPORTER_OTLP_ENDPOINT")?;
+    let exporter = match opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .build()
+    {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            warn!("failed to set up OpenTelemetry export: {}", e);
+            return None;
+        }
+    };
+    debug!("exporting launch spans over OTLP");
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .build();
+    global::set_tracer_provider(provider.clone());
+    Some(provider)
+}
+
+/// Record a launch that started at `start` and just finished.
+///
+/// `status` is the HTTP status of the final response, if there was one.
+pub fn record_launch(webhook: &str, status: Option<u16>, succeeded: bool, start: SystemTime) {
+    record_launch_with(&global::tracer("snouty"), webhook, status, succeeded, start);
+}
+
+fn record_launch_with(
+    tracer: &impl Tracer,
+    webhook: &str,
+    status: Option<u16>,
+    succeeded: bool,
+    start: SystemTime,
+) {
+    let end = SystemTime::now();
+    let duration = end.duration_since(start).unwrap_or_default();
+    let mut attributes = vec![
+        KeyValue::new("snouty.webhook", webhook.to_string()),
+        KeyValue::new("snouty.duration_ms", duration.as_millis() as i64),
+    ];
+    if let Some(status) = status {
+        attributes.push(KeyValue::new(
+            "http.response.status_code",
+            i64::from(status),
+        ));
+    }
+    let mut span = tracer
+        .span_builder("snouty.launch")
+        .with_start_time(start)
+        .with_attributes(attributes)
+        .start(tracer);
+    if !succeeded {
+        span.set_status(Status::error("launch failed"));
+    }
+    span.end_with_timestamp(end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry::{Key, Value};
+    use opentelemetry_sdk::trace::InMemorySpanExporter;
+
+    #[test]
+    fn records_launch_span() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = provider.tracer("snouty");
+
+        record_launch_with(&tracer, "basic_test", Some(202), true, SystemTime::now());
+        record_launch_with(&tracer, "basic_test", None, false, SystemTime::now());
+
+        let spans = exporter.get_finished_spans().unwrap();
+        assert_eq!(spans.len(), 2);
+        let attribute = |i: usize, key: &'static str| {
+            spans[i]
+                .attributes
+                .iter()
+                .find(|kv| kv.key == Key::from_static_str(key))
+                .map(|kv| kv.value.clone())
+        };
+        assert_eq!(spans[0].name, "snouty.launch");
+        assert_eq!(
+            attribute(0, "snouty.webhook"),
+            Some(Value::from("basic_test"))
+        );
+        assert_eq!(
+            attribute(0, "http.response.status_code"),
+            Some(Value::I64(202))
+        );
+        assert!(attribute(0, "snouty.duration_ms").is_some());
+        assert_eq!(spans[0].status, Status::Unset);
+
+        assert_eq!(attribute(1, "http.response.status_code"), None);
+        assert_eq!(spans[1].status, Status::error("launch failed"));
+    }
+}
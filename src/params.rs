@@ -1,28 +1,193 @@
-use jsonschema::Validator;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::str::FromStr;
+
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::{ValidationError, Validator};
 use log::debug;
 use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
 
 use crate::error::{Error, Result};
+use crate::recipients::{self, RECIPIENTS_KEY};
 
 const SCHEMA: &str = include_str!("params_schema.json");
 
-/// Params parsed from CLI arguments and validated against the JSON schema.
+/// The params definitions every schema provides under `$defs`, for
+/// [`validate_against_def`]: `testParams` for the `basic_test` and
+/// `basic_k8s_test` webhooks, and `debuggingParams` for debugging sessions.
+pub const DEF_NAMES: [&str; 2] = ["testParams", "debuggingParams"];
+
+/// A JSON schema that params are validated against.
+///
+/// The schema must define `testParams` and `debuggingParams` under `$defs`.
 #[derive(Debug, Clone)]
+pub struct Schema {
+    value: Value,
+}
+
+impl Schema {
+    /// The schema bundled into this build of snouty.
+    pub fn bundled() -> Result<Self> {
+        let value = serde_json::from_str(SCHEMA).map_err(|e| {
+            Error::InvalidSchema(format!("bundled schema is not valid JSON: {}", e))
+        })?;
+        Ok(Self { value })
+    }
+
+    /// Use a schema loaded at runtime, such as one fetched with `--schema`.
+    pub fn from_json(value: Value) -> Self {
+        Self { value }
+    }
+
+    /// The schema's top-level `version`, if it has one.
+    pub fn version(&self) -> Option<&str> {
+        self.value["version"].as_str()
+    }
+
+    /// Check that every params definition in the schema compiles.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        for def_name in DEF_NAMES {
+            if self.value["$defs"].get(def_name).is_none() {
+                return Err(format!("missing $defs/{}", def_name));
+            }
+            Validator::new(&self.def_schema(def_name))
+                .map_err(|e| format!("$defs/{}: {}", def_name, e))?;
+        }
+        Ok(())
+    }
+
+    /// Add a supplemental schema for a team's own (non-`antithesis.`) params.
+    ///
+    /// A `testParams` or `debuggingParams` definition in the supplement's
+    /// `$defs` is checked in addition to this schema's definition of the same
+    /// name. Its other `$defs` are added alongside this schema's, and may not
+    /// reuse their names.
+    pub fn with_supplement(mut self, supplement: &Value) -> Result<Self> {
+        let invalid =
+            |message: String| Error::InvalidSchema(format!("supplemental schema: {}", message));
+        let defs = supplement["$defs"]
+            .as_object()
+            .ok_or_else(|| invalid("missing $defs".to_string()))?;
+        let base = self.value["$defs"]
+            .as_object_mut()
+            .ok_or_else(|| invalid("base schema has no $defs".to_string()))?;
+
+        for (name, def) in defs {
+            if let Some(key) = antithesis_key(def) {
+                return Err(invalid(format!(
+                    "$defs/{} constrains {}, but antithesis.* params belong to the base schema",
+                    name, key
+                )));
+            }
+            let (name, extends) = match name.as_str() {
+                "testParams" => ("supplementalTestParams", Some("testParams")),
+                "debuggingParams" => ("supplementalDebuggingParams", Some("debuggingParams")),
+                other => (other, None),
+            };
+            if base.contains_key(name) {
+                return Err(invalid(format!(
+                    "$defs/{} conflicts with a definition in the base schema",
+                    name
+                )));
+            }
+            base.insert(name.to_string(), def.clone());
+            if let Some(extends) = extends {
+                let all_of = base[extends]["allOf"]
+                    .as_array_mut()
+                    .ok_or_else(|| invalid(format!("base $defs/{} has no allOf", extends)))?;
+                all_of.push(serde_json::json!({ "$ref": format!("#/$defs/{}", name) }));
+            }
+        }
+
+        self.check().map_err(invalid)?;
+        Ok(self)
+    }
+
+    /// Build a schema that references the specific definition.
+    fn def_schema(&self, def_name: &str) -> Value {
+        serde_json::json!({
+            "$ref": format!("#/$defs/{}", def_name),
+            "$defs": self.value["$defs"]
+        })
+    }
+}
+
+/// Options controlling how CLI arguments are parsed into params.
+#[derive(Debug, Clone, Default)]
+pub struct ArgsOptions {
+    /// Reject values that begin with `-`, which usually means a value was
+    /// forgotten or a stray dash was pasted in (e.g. `--antithesis.duration -30`).
+    pub strict: bool,
+
+    /// Prepended (with a `.`) to keys that don't contain a dot, so
+    /// `--foo bar` with prefix `my.ns` sets `my.ns.foo`.
+    pub prefix: Option<String>,
+}
+
+/// Options controlling how params are validated.
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// Stop at the first schema error instead of collecting all of them,
+    /// which is faster for very large param files.
+    pub first_error: bool,
+}
+
+/// Params parsed from CLI arguments and validated against the JSON schema.
+#[derive(Debug, Clone, Default)]
 pub struct Params {
     inner: Map<String, Value>,
+    /// Where each value came from, for keys whose source is known
+    sources: BTreeMap<String, Source>,
+}
+
+/// Where a param's value came from, shown in the preview with `--show-source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Cli,
+    Stdin,
+    File,
+    Config,
+    Report,
+    Env,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Source::Cli => "cli",
+            Source::Stdin => "stdin",
+            Source::File => "file",
+            Source::Config => "config",
+            Source::Report => "report",
+            Source::Env => "env",
+        }
+    }
 }
 
 impl Params {
     /// Parse params from CLI arguments.
     ///
-    /// Arguments should be in the format: `--key value`
+    /// Arguments should be in the format: `--key value` or `--key=value`
     pub fn from_args<I, S>(args: I) -> Result<Self>
     where
         I: IntoIterator<Item = S>,
         S: AsRef<str>,
     {
-        let inner = parse_args(args)?;
-        Ok(Self { inner })
+        Self::from_args_with(args, &ArgsOptions::default())
+    }
+
+    /// Parse params from CLI arguments with the given options.
+    pub fn from_args_with<I, S>(args: I, options: &ArgsOptions) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let inner = parse_args(args, options)?;
+        Ok(Self {
+            inner,
+            ..Self::default()
+        })
     }
 
     /// Create params from a JSON value.
@@ -34,17 +199,131 @@ impl Params {
             .ok_or_else(|| Error::InvalidArgs("expected JSON object".to_string()))?
             .clone();
         debug!("parsed {} params from JSON", inner.len());
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            ..Self::default()
+        })
+    }
+
+    /// Parse params from a YAML mapping. Like TOML tables, nested mappings
+    /// are flattened to dotted keys.
+    pub fn from_yaml(input: &str) -> Result<Self> {
+        let value: Value = serde_yaml::from_str(input)
+            .map_err(|e| Error::InvalidArgs(format!("invalid YAML: {}", e)))?;
+        let Value::Object(mapping) = value else {
+            return Err(Error::InvalidArgs("expected a YAML mapping".to_string()));
+        };
+        let mut flat = Map::new();
+        flatten_tables("", mapping, &mut flat);
+        Self::from_json(&Value::Object(flat))
+    }
+
+    /// Parse params from JSON (or JSON5) text.
+    ///
+    /// Parse errors name the line and column of the problem and quote that
+    /// line, since the parser's own message alone is hard to place in a long
+    /// document.
+    pub fn from_json5(input: &str) -> Result<Self> {
+        let value: Value = json5::from_str(input)
+            .map_err(|e| Error::InvalidArgs(describe_json5_error(input, &e)))?;
+        Self::from_json(&value)
+    }
+
+    /// Collect params from the environment variables whose names start with
+    /// `prefix`.
+    ///
+    /// The rest of each name is lowercased, with `__` standing for `.`, so
+    /// with prefix `SNOUTY_PARAM_`, `SNOUTY_PARAM_ANTITHESIS__TEST_NAME` sets
+    /// `antithesis.test_name`.
+    pub fn from_env_prefix(prefix: &str) -> Self {
+        let vars = std::env::vars_os().filter_map(|(name, value)| {
+            Some((name.into_string().ok()?, value.into_string().ok()?))
+        });
+        Self::from_vars_with_prefix(prefix, vars)
+    }
+
+    fn from_vars_with_prefix(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Self {
+        let mut params = Params::default();
+        for (name, value) in vars {
+            let Some(rest) = name.strip_prefix(prefix).filter(|rest| !rest.is_empty()) else {
+                continue;
+            };
+            let key = rest
+                .split("__")
+                .map(str::to_lowercase)
+                .collect::<Vec<_>>()
+                .join(".");
+            debug!("setting {} from ${}", key, name);
+            params.insert_from(key, value, Source::Env);
+        }
+        params
+    }
+
+    /// Create params from a TOML document.
+    ///
+    /// Tables are flattened to dotted keys, so `[antithesis] duration = "30"`
+    /// and `"antithesis.duration" = "30"` are the same param. Datetimes become
+    /// their RFC 3339 strings.
+    pub fn from_toml(input: &str) -> Result<Self> {
+        let table: toml::Table = toml::from_str(input)
+            .map_err(|e| Error::InvalidArgs(format!("invalid TOML: {}", e)))?;
+        let Value::Object(table) = toml_to_json(toml::Value::Table(table)) else {
+            unreachable!("a table converts to an object");
+        };
+        let mut flat = Map::new();
+        flatten_tables("", table, &mut flat);
+        Self::from_json(&Value::Object(flat))
     }
 
     /// Validate params against the test params schema.
     pub fn validate_test_params(&self) -> Result<()> {
-        validate_against_def(&self.inner, "testParams")
+        self.validate_test_params_with(&Schema::bundled()?, &ValidateOptions::default())
+    }
+
+    /// Validate params against the test params definition of `schema`.
+    pub fn validate_test_params_with(
+        &self,
+        schema: &Schema,
+        options: &ValidateOptions,
+    ) -> Result<()> {
+        validate_against_def(schema, &self.inner, "testParams", options)
     }
 
     /// Validate params against the debugging params schema.
     pub fn validate_debugging_params(&self) -> Result<()> {
-        validate_against_def(&self.inner, "debuggingParams")
+        self.validate_debugging_params_with(&Schema::bundled()?, &ValidateOptions::default())
+    }
+
+    /// Validate params against the debugging params definition of `schema`,
+    /// then check that a vtime window doesn't end before it starts.
+    pub fn validate_debugging_params_with(
+        &self,
+        schema: &Schema,
+        options: &ValidateOptions,
+    ) -> Result<()> {
+        validate_against_def(schema, &self.inner, "debuggingParams", options)?;
+        self.check_vtime_range()
+    }
+
+    fn check_vtime_range(&self) -> Result<()> {
+        let vtime = |key: &str| {
+            let value = self.inner.get(key)?.as_str()?;
+            Some((value, value.parse::<f64>().ok()?))
+        };
+        if let (Some((start, start_vtime)), Some((end, end_vtime))) = (
+            vtime("antithesis.debugging.vtime_start"),
+            vtime("antithesis.debugging.vtime_end"),
+        ) && start_vtime > end_vtime
+        {
+            return Err(Error::ValidationFailed(vec![format!(
+                "antithesis.debugging.vtime_start {} is after antithesis.debugging.vtime_end {}",
+                start, end
+            )]));
+        }
+        Ok(())
     }
 
     /// Get a reference to the inner map.
@@ -60,10 +339,118 @@ impl Params {
     /// Merge another Params into this one, with the other params taking priority.
     pub fn merge(&mut self, other: Params) {
         for (key, value) in other.inner {
+            match other.sources.get(&key) {
+                Some(source) => self.sources.insert(key.clone(), *source),
+                None => self.sources.remove(&key),
+            };
             self.inner.insert(key, value);
         }
     }
 
+    /// Record `source` as where every current value came from.
+    pub fn with_source(mut self, source: Source) -> Self {
+        self.sources = self.inner.keys().map(|key| (key.clone(), source)).collect();
+        self
+    }
+
+    /// Where the value for `key` came from, if known.
+    pub fn source(&self, key: &str) -> Option<Source> {
+        self.sources.get(key).copied()
+    }
+
+    /// Set a single param, replacing any existing value.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        self.sources.remove(&key);
+        self.inner.insert(key, Value::String(value.into()));
+    }
+
+    /// Insert a string value, recording where it came from.
+    pub fn insert_from(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+        source: Source,
+    ) {
+        let key = key.into();
+        self.sources.insert(key.clone(), source);
+        self.inner.insert(key, Value::String(value.into()));
+    }
+
+    /// Remove a param, returning its value if it was set.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.sources.remove(key);
+        self.inner.shift_remove(key)
+    }
+
+    /// Convert a human-readable `antithesis.duration` such as `30m` or `1h`
+    /// into the whole number of minutes the schema expects.
+    ///
+    /// Bare numbers are already minutes and are left unchanged.
+    pub fn humanize_duration(&mut self) -> Result<()> {
+        let Some(Value::String(duration)) = self.inner.get("antithesis.duration") else {
+            return Ok(());
+        };
+        if duration.parse::<u64>().is_ok() {
+            return Ok(());
+        }
+
+        let parsed = humantime::parse_duration(duration).map_err(|e| {
+            Error::InvalidArgs(format!("invalid antithesis.duration {:?}: {}", duration, e))
+        })?;
+        if parsed.as_secs() % 60 != 0 || parsed.subsec_nanos() != 0 {
+            return Err(Error::InvalidArgs(format!(
+                "antithesis.duration {:?} is not a whole number of minutes",
+                duration
+            )));
+        }
+
+        let minutes = (parsed.as_secs() / 60).to_string();
+        debug!(
+            "converted antithesis.duration {} to {} minutes",
+            duration, minutes
+        );
+        // Set in place so the value keeps its source
+        self.inner
+            .insert("antithesis.duration".to_string(), Value::String(minutes));
+        Ok(())
+    }
+
+    /// Convert a structured `antithesis.duration`, either an integer or a
+    /// `{"value": N, "unit": "minutes|hours"}` object, into the string of
+    /// minutes the API expects.
+    ///
+    /// Strings are left unchanged.
+    pub fn normalize_duration(&mut self) -> Result<()> {
+        let minutes = match self.inner.get("antithesis.duration") {
+            None | Some(Value::String(_)) => return Ok(()),
+            Some(Value::Number(n)) => n.as_u64(),
+            Some(Value::Object(duration)) => {
+                let value = duration.get("value").and_then(Value::as_u64);
+                match duration.get("unit").and_then(Value::as_str) {
+                    Some("minutes") => value,
+                    Some("hours") => value.and_then(|hours| hours.checked_mul(60)),
+                    _ => None,
+                }
+            }
+            Some(_) => None,
+        };
+        let Some(minutes) = minutes else {
+            return Err(Error::InvalidArgs(format!(
+                "invalid antithesis.duration {}: expected a number of minutes or {{\"value\": N, \"unit\": \"minutes|hours\"}}",
+                self.inner["antithesis.duration"]
+            )));
+        };
+
+        debug!("normalized antithesis.duration to {} minutes", minutes);
+        // Set in place so the value keeps its source
+        self.inner.insert(
+            "antithesis.duration".to_string(),
+            Value::String(minutes.to_string()),
+        );
+        Ok(())
+    }
+
     /// Get a redacted copy of the params for safe display in logs/CI.
     /// Sensitive fields (tokens, emails) are replaced with "[REDACTED]".
     pub fn to_redacted_map(&self) -> Map<String, Value> {
@@ -80,13 +467,367 @@ impl Params {
             })
             .collect()
     }
+
+    /// Like [`Params::to_redacted_map`], but each value is an object like
+    /// `{"value": "30", "source": "cli"}` saying where it came from.
+    pub fn to_sourced_map(&self) -> Map<String, Value> {
+        self.to_redacted_map()
+            .into_iter()
+            .map(|(key, value)| {
+                let source = self.source(&key).map(Source::as_str);
+                let annotated = serde_json::json!({ "source": source, "value": value });
+                (key, annotated)
+            })
+            .collect()
+    }
+
+    /// Keys that are shown unredacted by [`to_redacted_map`](Self::to_redacted_map)
+    /// but whose value contains a secret: one of `extra_secrets`, or the value
+    /// of a sensitive key (each recipient separately).
+    pub fn leaked_keys(&self, extra_secrets: &[String]) -> Vec<String> {
+        let mut secrets: Vec<&str> = extra_secrets.iter().map(String::as_str).collect();
+        for (key, value) in &self.inner {
+            if let (true, Some(value)) = (is_sensitive_key(key), value.as_str()) {
+                secrets.extend(value.split(';').map(str::trim));
+            }
+        }
+        secrets.retain(|s| !s.is_empty());
+
+        self.inner
+            .iter()
+            .filter(|(key, _)| !is_sensitive_key(key))
+            .filter(|(_, value)| {
+                let shown = match value {
+                    Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                };
+                secrets.iter().any(|secret| shown.contains(secret))
+            })
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// A short fingerprint for correlating identical launches across logs: the
+    /// first 8 hex digits of a SHA-256 of the params.
+    ///
+    /// It's computed over the params sorted by key, so it doesn't depend on
+    /// insertion order. Sensitive values only contribute their length, so the
+    /// fingerprint doesn't reveal them.
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        let mut entries: Vec<(&String, &Value)> = self.inner.iter().collect();
+        entries.sort_by_key(|(key, _)| *key);
+        for (key, value) in entries {
+            let mut value = match value {
+                Value::String(s) => s.clone(),
+                other => {
+                    // Nested objects keep their parsed key order, which
+                    // shouldn't change the fingerprint
+                    let mut other = other.clone();
+                    other.sort_all_objects();
+                    other.to_string()
+                }
+            };
+            if is_sensitive_key(key) {
+                value = "*".repeat(value.chars().count());
+            }
+            for part in [key.as_bytes(), &[0], value.as_bytes(), &[0]] {
+                hasher.update(part);
+            }
+        }
+        hasher.finalize()[..4]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+
+    /// The key-by-key changes from `self` to `other`, sorted by key, with
+    /// sensitive values redacted.
+    pub fn diff(&self, other: &Params) -> Vec<ParamChange> {
+        let (old, new) = (self.to_redacted_map(), other.to_redacted_map());
+        let keys: BTreeSet<&String> = self.inner.keys().chain(other.inner.keys()).collect();
+        keys.into_iter()
+            .filter_map(|key| {
+                let change = match (self.inner.get(key), other.inner.get(key)) {
+                    (None, Some(_)) => ParamChange::Added {
+                        key: key.clone(),
+                        value: new[key].clone(),
+                    },
+                    (Some(_), None) => ParamChange::Removed {
+                        key: key.clone(),
+                        value: old[key].clone(),
+                    },
+                    // Compare the real values, so a changed secret still shows
+                    (Some(a), Some(b)) if a != b => ParamChange::Changed {
+                        key: key.clone(),
+                        old: old[key].clone(),
+                        new: new[key].clone(),
+                    },
+                    _ => return None,
+                };
+                Some(change)
+            })
+            .collect()
+    }
+}
+
+/// Typed params for launching the multiverse debugger at one moment.
+///
+/// Build with [`DebuggingParams::builder`], then convert into [`Params`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebuggingParams {
+    pub session_id: String,
+    pub input_hash: String,
+    pub vtime: String,
+}
+
+impl DebuggingParams {
+    pub fn builder() -> DebuggingParamsBuilder {
+        DebuggingParamsBuilder::default()
+    }
+}
+
+impl From<DebuggingParams> for Params {
+    fn from(debugging: DebuggingParams) -> Self {
+        let mut params = Params::default();
+        params.insert("antithesis.debugging.session_id", debugging.session_id);
+        params.insert("antithesis.debugging.input_hash", debugging.input_hash);
+        params.insert("antithesis.debugging.vtime", debugging.vtime);
+        params
+    }
+}
+
+/// Builder for [`DebuggingParams`].
+#[derive(Debug, Clone, Default)]
+pub struct DebuggingParamsBuilder {
+    session_id: Option<String>,
+    input_hash: Option<String>,
+    vtime: Option<String>,
+}
+
+impl DebuggingParamsBuilder {
+    pub fn session_id(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn input_hash(mut self, input_hash: impl Into<String>) -> Self {
+        self.input_hash = Some(input_hash.into());
+        self
+    }
+
+    /// The vtime as the triage report shows it, e.g. `329.8037810830865`.
+    pub fn vtime(mut self, vtime: impl Into<String>) -> Self {
+        self.vtime = Some(vtime.into());
+        self
+    }
+
+    /// Check that every field is set and non-empty, and that `vtime` is a number.
+    pub fn build(self) -> Result<DebuggingParams> {
+        let required = |field: &str, value: Option<String>| match value {
+            Some(value) if !value.trim().is_empty() => Ok(value),
+            _ => Err(Error::InvalidArgs(format!("missing debugging {}", field))),
+        };
+        let debugging = DebuggingParams {
+            session_id: required("session_id", self.session_id)?,
+            input_hash: required("input_hash", self.input_hash)?,
+            vtime: required("vtime", self.vtime)?,
+        };
+        if debugging.vtime.parse::<f64>().is_err() {
+            return Err(Error::InvalidArgs(format!(
+                "debugging vtime must be a number, got {:?}",
+                debugging.vtime
+            )));
+        }
+        Ok(debugging)
+    }
+}
+
+/// One key's difference between two sets of params, from [`Params::diff`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamChange {
+    Added { key: String, value: Value },
+    Removed { key: String, value: Value },
+    Changed { key: String, old: Value, new: Value },
+}
+
+impl fmt::Display for ParamChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added { key, value } => write!(f, "+ {}: {}", key, value),
+            Self::Removed { key, value } => write!(f, "- {}: {}", key, value),
+            Self::Changed { key, old, new } => write!(f, "~ {}: {} -> {}", key, old, new),
+        }
+    }
+}
+
+/// A `key=value` label attached to a test run as `antithesis.labels.<key>`.
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub key: String,
+    pub value: String,
+}
+
+impl Label {
+    /// The param key this label is sent as.
+    pub fn param_key(&self) -> String {
+        format!("antithesis.labels.{}", self.key)
+    }
+}
+
+impl FromStr for Label {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got {:?}", s))?;
+        if !is_identifier(key) {
+            return Err(format!(
+                "label key {:?} must be a simple identifier (letters, digits, and _)",
+                key
+            ));
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// A `KEY=VALUE` environment variable forwarded to the test as
+/// `antithesis.env.<KEY>`.
+#[derive(Debug, Clone)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+impl EnvVar {
+    /// The param key this variable is sent as.
+    pub fn param_key(&self) -> String {
+        format!("antithesis.env.{}", self.key)
+    }
+}
+
+impl FromStr for EnvVar {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got {:?}", s))?;
+        if !is_identifier(key) {
+            return Err(format!(
+                "env name {:?} must be a valid environment variable name (letters, digits, and _)",
+                key
+            ));
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// The first `antithesis.` key a schema definition names in `properties`,
+/// `patternProperties`, or `required`, if any.
+fn antithesis_key(def: &Value) -> Option<String> {
+    let named = ["properties", "patternProperties"]
+        .iter()
+        .filter_map(|field| def[field].as_object())
+        .flat_map(|props| props.keys().cloned());
+    let required = def["required"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|key| key.as_str().map(str::to_string));
+    named
+        .chain(required)
+        .find(|key| key.trim_start_matches('^').starts_with("antithesis"))
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Describe a JSON5 parse error as `invalid JSON at line L, column C: ...`,
+/// followed by the offending line with a caret under the column.
+fn describe_json5_error(input: &str, error: &json5::Error) -> String {
+    let message = error.to_string();
+    let Some(position) = error.position() else {
+        return format!("invalid JSON: {}", message);
+    };
+    // The message ends with the position, which is repeated in the prefix
+    let reason = message
+        .strip_suffix(&format!(" at {}", position))
+        .unwrap_or(&message);
+    // json5 counts lines and columns from 0
+    let (line, column) = (position.line + 1, position.column + 1);
+    let mut description = format!(
+        "invalid JSON at line {}, column {}: {}",
+        line, column, reason
+    );
+    if let Some(text) = input.lines().nth(line - 1) {
+        let gutter = " ".repeat(line.to_string().len());
+        let indent: String = text
+            .chars()
+            .take(column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        description.push_str(&format!(
+            "\n  {} | {}\n  {} | {}^",
+            line, text, gutter, indent
+        ));
+    }
+    description
+}
+
+/// Convert a TOML value to JSON. serde would turn a datetime into an internal
+/// `{"$__toml_private_datetime": ...}` object, so it's written as a string.
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => serde_json::Number::from_f64(f)
+            .map_or_else(|| Value::String(f.to_string()), Value::Number),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(datetime) => Value::String(datetime.to_string()),
+        toml::Value::Array(array) => Value::Array(array.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn flatten_tables(prefix: &str, table: Map<String, Value>, flat: &mut Map<String, Value>) {
+    for (key, value) in table {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(table) => flatten_tables(&key, table, flat),
+            value => {
+                flat.insert(key, value);
+            }
+        }
+    }
 }
 
 fn is_sensitive_key(key: &str) -> bool {
-    key.ends_with(".token") || key == "antithesis.report.recipients"
+    key.ends_with(".token") || key == RECIPIENTS_KEY || recipients::channel(key).is_some()
 }
 
-fn parse_args<I, S>(args: I) -> Result<Map<String, Value>>
+fn parse_args<I, S>(args: I, options: &ArgsOptions) -> Result<Map<String, Value>>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<str>,
@@ -98,15 +839,43 @@ where
         let arg = arg.as_ref();
 
         if let Some(key) = arg.strip_prefix("--") {
+            // `--key=value` carries its value inline; `--key=` sets an empty string
+            let (key, inline) = match key.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (key, None),
+            };
             if key.is_empty() {
                 return Err(Error::InvalidArgs("empty key after --".to_string()));
             }
 
-            let value = iter
-                .next()
-                .ok_or_else(|| Error::InvalidArgs(format!("missing value for --{}", key)))?;
+            let value = match inline {
+                Some(value) => value.to_string(),
+                None => iter
+                    .next()
+                    .ok_or_else(|| Error::InvalidArgs(format!("missing value for --{}", key)))?
+                    .as_ref()
+                    .to_string(),
+            };
 
-            map.insert(key.to_string(), Value::String(value.as_ref().to_string()));
+            if options.strict && value.starts_with('-') {
+                let hint = if value.starts_with("--") {
+                    "looks like a flag; is the value missing?"
+                } else {
+                    "values starting with - are rejected by --strict-args"
+                };
+                return Err(Error::InvalidArgs(format!(
+                    "suspicious value {:?} for --{}: {}",
+                    value, key, hint
+                )));
+            }
+
+            let key = match &options.prefix {
+                Some(prefix) if !key.contains('.') => {
+                    format!("{}.{}", prefix.trim_end_matches('.'), key)
+                }
+                _ => key.to_string(),
+            };
+            map.insert(key, Value::String(value));
         } else {
             return Err(Error::InvalidArgs(format!("unexpected argument: {}", arg)));
         }
@@ -115,22 +884,132 @@ where
     Ok(map)
 }
 
-fn validate_against_def(params: &Map<String, Value>, def_name: &str) -> Result<()> {
-    let schema: Value = serde_json::from_str(SCHEMA).expect("valid schema");
+/// List the documented properties of a schema definition, with their descriptions.
+///
+/// Properties pulled in through `allOf` references are included. Properties
+/// without a `description` annotation map to an empty string.
+pub fn describe_def(def_name: &str) -> Result<BTreeMap<String, String>> {
+    let schema = Schema::bundled()?;
+    let mut out = BTreeMap::new();
+    collect_properties(&schema.value["$defs"], def_name, &mut out);
+    Ok(out)
+}
+
+fn collect_properties(defs: &Value, def_name: &str, out: &mut BTreeMap<String, String>) {
+    let def = &defs[def_name];
+
+    if let Some(props) = def["properties"].as_object() {
+        for (key, prop) in props {
+            let description = prop["description"].as_str().unwrap_or_default();
+            out.insert(key.clone(), description.to_string());
+        }
+    }
+
+    for sub in def["allOf"].as_array().into_iter().flatten() {
+        if let Some(name) = sub["$ref"]
+            .as_str()
+            .and_then(|r| r.strip_prefix("#/$defs/"))
+        {
+            collect_properties(defs, name, out);
+        }
+    }
+}
+
+/// Look up the schema description of a property in any definition.
+fn property_description(schema: &Value, key: &str) -> Option<String> {
+    schema["$defs"]
+        .as_object()?
+        .values()
+        .find_map(|def| def["properties"][key]["description"].as_str())
+        .map(str::to_string)
+}
 
-    // Build a schema that references the specific definition
-    let def_schema = serde_json::json!({
-        "$ref": format!("#/$defs/{}", def_name),
-        "$defs": schema["$defs"]
-    });
+/// The top-level param key a validation error refers to, if any.
+fn error_key(error: &ValidationError) -> Option<String> {
+    if let ValidationErrorKind::Required { property } = error.kind() {
+        return property.as_str().map(str::to_string);
+    }
+    let path = error.instance_path().as_str().strip_prefix('/')?;
+    (!path.is_empty() && !path.contains('/')).then(|| path.to_string())
+}
 
-    let validator = Validator::new(&def_schema).expect("valid schema");
+/// Validate a params map against the definition `def_name` (one of
+/// [`DEF_NAMES`]) in `schema`, without building [`Params`].
+///
+/// On failure the error is [`Error::ValidationFailed`], holding one message
+/// per problem, sorted unless `options.first_error` is set. A schema whose
+/// definition doesn't compile gives [`Error::InvalidSchema`].
+///
+/// ```
+/// use serde_json::json;
+/// use snouty::error::Error;
+/// use snouty::params::{Schema, ValidateOptions, validate_against_def};
+///
+/// let params = json!({
+///     "antithesis.debugging.session_id": "f89d5c11f5e3bf5e4bb3641809800cee-44-22",
+///     "antithesis.debugging.input_hash": "6057726200491963783",
+///     "antithesis.debugging.vtime": "329.8037810830865",
+/// });
+/// let schema = Schema::bundled().unwrap();
+/// let options = ValidateOptions::default();
+/// let map = params.as_object().unwrap();
+/// assert!(validate_against_def(&schema, map, "debuggingParams", &options).is_ok());
+///
+/// let mut missing = map.clone();
+/// missing.shift_remove("antithesis.debugging.vtime");
+/// match validate_against_def(&schema, &missing, "debuggingParams", &options) {
+///     Err(Error::ValidationFailed(errors)) => assert!(!errors.is_empty()),
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn validate_against_def(
+    schema: &Schema,
+    params: &Map<String, Value>,
+    def_name: &str,
+    options: &ValidateOptions,
+) -> Result<()> {
+    let validator = Validator::new(&schema.def_schema(def_name))
+        .map_err(|e| Error::InvalidSchema(format!("$defs/{}: {}", def_name, e)))?;
     let instance = Value::Object(params.clone());
 
-    let errors: Vec<String> = validator
-        .iter_errors(&instance)
-        .map(|e| e.to_string())
-        .collect();
+    let describe = |e: &ValidationError| {
+        let key = error_key(e);
+        match key.and_then(|k| property_description(&schema.value, &k).map(|d| (k, d))) {
+            Some((key, description)) => format!("{} ({}: {})", e, key, description),
+            None => e.to_string(),
+        }
+    };
+
+    let mut errors: Vec<String> = if options.first_error {
+        validator
+            .validate(&instance)
+            .err()
+            .map(|e| describe(&e))
+            .into_iter()
+            .collect()
+    } else {
+        // iter_errors makes no ordering promises; sort by instance path, then
+        // message, so output and test assertions are deterministic.
+        let mut errors: Vec<(String, String)> = validator
+            .iter_errors(&instance)
+            .map(|e| (e.instance_path().as_str().to_string(), describe(&e)))
+            .collect();
+        errors.sort();
+        errors.into_iter().map(|(_, message)| message).collect()
+    };
+
+    // The schema only requires per-channel recipients to be strings; their
+    // entries are checked against the channel's rules here.
+    if errors.is_empty() || !options.first_error {
+        for (key, value) in params {
+            if let (Some(channel), Some(value)) = (recipients::channel(key), value.as_str()) {
+                errors.extend(recipients::validate_channel(channel, value));
+            }
+        }
+        if options.first_error {
+            errors.truncate(1);
+        }
+    }
 
     if !errors.is_empty() {
         debug!("validation failed with {} errors", errors.len());
@@ -240,6 +1119,79 @@ mod tests {
         assert!(params.validate_debugging_params().is_ok());
     }
 
+    fn debugging_args_with_range(start: &str, end: &str) -> Params {
+        Params::from_args([
+            "--antithesis.debugging.input_hash",
+            "abc123",
+            "--antithesis.debugging.session_id",
+            "sess-456",
+            "--antithesis.debugging.vtime",
+            "329.8",
+            "--antithesis.debugging.vtime_start",
+            start,
+            "--antithesis.debugging.vtime_end",
+            end,
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn validate_debugging_params_vtime_range() {
+        assert!(
+            debugging_args_with_range("300", "329.8")
+                .validate_debugging_params()
+                .is_ok()
+        );
+        assert!(
+            debugging_args_with_range("329.8", "329.8")
+                .validate_debugging_params()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_debugging_params_rejects_inverted_vtime_range() {
+        let err = debugging_args_with_range("400.5", "329.8")
+            .validate_debugging_params()
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "antithesis.debugging.vtime_start 400.5 is after antithesis.debugging.vtime_end 329.8"
+        ));
+    }
+
+    #[test]
+    fn validate_debugging_params_requires_both_range_ends() {
+        let mut params = debugging_args_with_range("300", "329.8");
+        params.inner.remove("antithesis.debugging.vtime_end");
+        assert!(params.validate_debugging_params().is_err());
+
+        let params = debugging_args_with_range("soon", "329.8");
+        assert!(params.validate_debugging_params().is_err());
+    }
+
+    #[test]
+    fn validate_against_def_takes_a_bare_map() {
+        let params = serde_json::json!({
+            "antithesis.debugging.session_id": "sess-456",
+            "antithesis.debugging.input_hash": "abc123",
+            "antithesis.debugging.vtime": "123",
+        });
+        let map = params.as_object().unwrap();
+        let (schema, options) = (Schema::bundled().unwrap(), ValidateOptions::default());
+        assert!(validate_against_def(&schema, map, "debuggingParams", &options).is_ok());
+
+        let mut missing = map.clone();
+        missing.shift_remove("antithesis.debugging.vtime");
+        match validate_against_def(&schema, &missing, "debuggingParams", &options) {
+            Err(Error::ValidationFailed(errors)) => assert!(
+                errors
+                    .iter()
+                    .any(|e| e.contains(r#""antithesis.debugging.vtime" is a required property"#))
+            ),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
     #[test]
     fn validate_debugging_params_missing_required() {
         let args = ["--antithesis.debugging.input_hash", "abc123"];
@@ -263,6 +1215,590 @@ mod tests {
         assert!(params.validate_debugging_params().is_err());
     }
 
+    #[test]
+    fn validation_errors_include_descriptions() {
+        let params = Params::from_args(["--antithesis.duration", "soon"]).unwrap();
+        let err = params.validate_test_params().unwrap_err().to_string();
+        assert!(err.contains("antithesis.duration: Desired test duration in minutes"));
+
+        let params = Params::from_args(["--antithesis.debugging.input_hash", "abc"]).unwrap();
+        let err = params.validate_debugging_params().unwrap_err().to_string();
+        assert!(err.contains("antithesis.debugging.session_id: Session ID of the test run"));
+    }
+
+    #[test]
+    fn normalizes_integer_duration() {
+        let mut params =
+            Params::from_json(&serde_json::json!({"antithesis.duration": 90})).unwrap();
+        params.validate_test_params().unwrap();
+        params.normalize_duration().unwrap();
+        assert_eq!(params.as_map()["antithesis.duration"], "90");
+    }
+
+    #[test]
+    fn normalizes_duration_with_units() {
+        let normalized = |duration: Value| {
+            let mut params =
+                Params::from_json(&serde_json::json!({"antithesis.duration": duration})).unwrap();
+            params.validate_test_params().unwrap();
+            params.normalize_duration().unwrap();
+            params.as_map()["antithesis.duration"].clone()
+        };
+        assert_eq!(
+            normalized(serde_json::json!({"value": 2, "unit": "hours"})),
+            "120"
+        );
+        assert_eq!(
+            normalized(serde_json::json!({"value": 45, "unit": "minutes"})),
+            "45"
+        );
+        assert_eq!(normalized(serde_json::json!("30")), "30");
+    }
+
+    #[test]
+    fn unitted_duration_string_names_the_pattern() {
+        let params = Params::from_args(["--antithesis.duration", "30m"]).unwrap();
+        let err = params.validate_test_params().unwrap_err().to_string();
+        assert!(
+            err.contains(r#""30m" does not match "^[0-9]+$""#),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn rejects_bad_structured_durations() {
+        for duration in [
+            serde_json::json!({"value": 2, "unit": "days"}),
+            serde_json::json!({"value": 2}),
+            serde_json::json!({"value": 1.5, "unit": "hours"}),
+            serde_json::json!(-5),
+        ] {
+            let mut params =
+                Params::from_json(&serde_json::json!({"antithesis.duration": duration.clone()}))
+                    .unwrap();
+            assert!(params.validate_test_params().is_err(), "{}", duration);
+            assert!(params.normalize_duration().is_err(), "{}", duration);
+        }
+    }
+
+    #[test]
+    fn validation_errors_are_sorted() {
+        let params = Params::from_args([
+            "--antithesis.is_ephemeral",
+            "maybe",
+            "--antithesis.duration",
+            "soon",
+            "--antithesis.bogus",
+            "x",
+        ])
+        .unwrap();
+
+        let collect = |params: &Params| match params.validate_test_params() {
+            Err(Error::ValidationFailed(errors)) => errors,
+            other => panic!("expected validation failure, got {:?}", other),
+        };
+
+        // Sorted by instance path (the root first), not by argument order
+        let errors = collect(&params);
+        assert_eq!(errors.len(), 3, "{:?}", errors);
+        assert!(errors[0].starts_with("Unevaluated properties are not allowed"));
+        assert!(errors[1].starts_with("\"soon\" does not match"));
+        assert!(errors[2].starts_with("\"maybe\" is not one of"));
+        for _ in 0..10 {
+            assert_eq!(collect(&params), errors);
+        }
+    }
+
+    #[test]
+    fn first_error_stops_after_one() {
+        let params = Params::from_args([
+            "--antithesis.duration",
+            "soon",
+            "--antithesis.labels.not-an-ident",
+            "x",
+            "--antithesis.debugging.input_hash",
+            "abc",
+        ])
+        .unwrap();
+        assert!(matches!(
+            params.validate_debugging_params(),
+            Err(Error::ValidationFailed(errors)) if errors.len() > 1
+        ));
+
+        let options = ValidateOptions { first_error: true };
+        match params.validate_debugging_params_with(&Schema::bundled().unwrap(), &options) {
+            Err(Error::ValidationFailed(errors)) => assert_eq!(errors.len(), 1),
+            other => panic!("expected validation failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn supplemental_schema_enforces_custom_namespace() {
+        let schema = Schema::bundled()
+            .unwrap()
+            .with_supplement(&serde_json::json!({
+                "$defs": {
+                    "team": { "type": "string", "enum": ["storage", "network"] },
+                    "testParams": {
+                        "properties": { "mycorp.team": { "$ref": "#/$defs/team" } },
+                        "required": ["mycorp.team"]
+                    }
+                }
+            }))
+            .unwrap();
+        let options = ValidateOptions::default();
+
+        let mut params = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        let err = params
+            .validate_test_params_with(&schema, &options)
+            .unwrap_err();
+        assert!(err.to_string().contains("mycorp.team"));
+
+        params.insert("mycorp.team", "compute");
+        assert!(params.validate_test_params_with(&schema, &options).is_err());
+
+        params.insert("mycorp.team", "storage");
+        params.validate_test_params_with(&schema, &options).unwrap();
+    }
+
+    #[test]
+    fn supplemental_schema_conflicts_are_errors() {
+        let err = Schema::bundled()
+            .unwrap()
+            .with_supplement(&serde_json::json!({ "$defs": { "labels": {} } }))
+            .unwrap_err();
+        assert!(err.to_string().contains("$defs/labels conflicts"));
+
+        let err = Schema::bundled()
+            .unwrap()
+            .with_supplement(&serde_json::json!({
+                "$defs": { "testParams": { "required": ["antithesis.source"] } }
+            }))
+            .unwrap_err();
+        assert!(err.to_string().contains("constrains antithesis.source"));
+    }
+
+    #[test]
+    fn broken_schema_is_an_error() {
+        let schema = Schema::from_json(serde_json::json!({
+            "$defs": { "testParams": { "type": "not-a-type" } }
+        }));
+        assert!(schema.check().is_err());
+
+        let params = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        match params.validate_test_params_with(&schema, &ValidateOptions::default()) {
+            Err(Error::InvalidSchema(message)) => assert!(message.contains("$defs/testParams")),
+            other => panic!("expected invalid schema, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn bundled_schema_compiles() {
+        Schema::bundled().unwrap().check().unwrap();
+    }
+
+    #[test]
+    fn describe_def_follows_refs() {
+        let props = describe_def("testParams").unwrap();
+        assert_eq!(
+            props.get("antithesis.duration").unwrap(),
+            "Desired test duration in minutes"
+        );
+        assert!(props.contains_key("antithesis.report.recipients"));
+        assert!(!props.contains_key("antithesis.debugging.vtime"));
+
+        let props = describe_def("debuggingParams").unwrap();
+        assert!(props.contains_key("antithesis.debugging.vtime"));
+        assert!(props.contains_key("antithesis.report.recipients"));
+    }
+
+    #[test]
+    fn debugging_params_builder() {
+        let params: Params = DebuggingParams::builder()
+            .session_id("f89d5c11f5e3bf5e4bb3641809800cee-44-22")
+            .input_hash("6057726200491963783")
+            .vtime("329.8037810830865")
+            .build()
+            .unwrap()
+            .into();
+
+        let map = params.as_map();
+        assert_eq!(
+            map.get("antithesis.debugging.session_id").unwrap(),
+            "f89d5c11f5e3bf5e4bb3641809800cee-44-22"
+        );
+        assert_eq!(
+            map.get("antithesis.debugging.input_hash").unwrap(),
+            "6057726200491963783"
+        );
+        assert_eq!(
+            map.get("antithesis.debugging.vtime").unwrap(),
+            "329.8037810830865"
+        );
+        assert!(params.validate_debugging_params().is_ok());
+    }
+
+    #[test]
+    fn debugging_params_builder_validates() {
+        let err = DebuggingParams::builder()
+            .session_id("sess")
+            .input_hash("abc")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("missing debugging vtime"));
+
+        let err = DebuggingParams::builder()
+            .session_id("sess")
+            .input_hash("abc")
+            .vtime("soon")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("vtime must be a number"));
+    }
+
+    #[test]
+    fn parse_label() {
+        let label: Label = "ci_job=1234".parse().unwrap();
+        assert_eq!(label.param_key(), "antithesis.labels.ci_job");
+        assert_eq!(label.value, "1234");
+
+        assert!("ci-job=1234".parse::<Label>().is_err());
+        assert!("1job=1234".parse::<Label>().is_err());
+        assert!("ci_job".parse::<Label>().is_err());
+    }
+
+    #[test]
+    fn parse_env_var() {
+        let var: EnvVar = "ANTITHESIS_SDK_LOCAL_OUTPUT=/tmp/out".parse().unwrap();
+        assert_eq!(
+            var.param_key(),
+            "antithesis.env.ANTITHESIS_SDK_LOCAL_OUTPUT"
+        );
+        assert_eq!(var.value, "/tmp/out");
+        assert_eq!("EMPTY=".parse::<EnvVar>().unwrap().value, "");
+
+        assert!("MY-VAR=1".parse::<EnvVar>().is_err());
+        assert!("1VAR=1".parse::<EnvVar>().is_err());
+        assert!("=1".parse::<EnvVar>().is_err());
+        assert!("VAR".parse::<EnvVar>().is_err());
+    }
+
+    #[test]
+    fn validate_test_params_with_env() {
+        let mut params = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        params.insert("antithesis.env.RUST_LOG", "debug");
+        assert!(params.validate_test_params().is_ok());
+
+        params.insert("antithesis.env.NOT-A-NAME", "x");
+        assert!(params.validate_test_params().is_err());
+    }
+
+    #[test]
+    fn from_env_prefix_maps_names_to_keys() {
+        let vars = [
+            ("SNOUTY_PARAM_ANTITHESIS__DURATION", "30"),
+            ("SNOUTY_PARAM_ANTITHESIS__TEST_NAME", "nightly"),
+            ("SNOUTY_PARAM_MY__TEAM", "storage"),
+            ("SNOUTY_PARAM_", "ignored"),
+            ("OTHER_ANTITHESIS__DURATION", "60"),
+        ]
+        .map(|(name, value)| (name.to_string(), value.to_string()));
+
+        let params = Params::from_vars_with_prefix("SNOUTY_PARAM_", vars);
+        let map = params.as_map();
+        assert_eq!(map["antithesis.duration"], "30");
+        assert_eq!(map["antithesis.test_name"], "nightly");
+        assert_eq!(map["my.team"], "storage");
+        assert_eq!(map.len(), 3);
+        assert_eq!(params.source("antithesis.duration"), Some(Source::Env));
+    }
+
+    #[test]
+    fn from_toml_flattens_tables() {
+        let params = Params::from_toml(
+            r#"
+"antithesis.description" = "nightly"
+
+[antithesis]
+duration = "30"
+
+[antithesis.report]
+recipients = "team@example.com"
+"#,
+        )
+        .unwrap();
+        let map = params.as_map();
+        assert_eq!(map["antithesis.description"], "nightly");
+        assert_eq!(map["antithesis.duration"], "30");
+        assert_eq!(map["antithesis.report.recipients"], "team@example.com");
+        assert_eq!(map.len(), 3);
+
+        assert!(Params::from_toml("duration = ").is_err());
+    }
+
+    #[test]
+    fn from_toml_keeps_datetimes_as_strings() {
+        let params = Params::from_toml(
+            r#"
+[my.run]
+started = 2024-05-01T12:30:00Z
+day = 2024-05-01
+"#,
+        )
+        .unwrap();
+        let map = params.as_map();
+        assert_eq!(map["my.run.started"], "2024-05-01T12:30:00Z");
+        assert_eq!(map["my.run.day"], "2024-05-01");
+        assert_eq!(map.len(), 2);
+    }
+
+    #[test]
+    fn from_yaml_flattens_mappings() {
+        let params = Params::from_yaml(
+            r#"
+antithesis.description: nightly
+antithesis:
+  duration: "30"
+"#,
+        )
+        .unwrap();
+        let map = params.as_map();
+        assert_eq!(map["antithesis.description"], "nightly");
+        assert_eq!(map["antithesis.duration"], "30");
+        assert_eq!(map.len(), 2);
+
+        assert!(Params::from_yaml("- a\n- b").is_err());
+    }
+
+    #[test]
+    fn from_json5_errors_point_at_the_problem() {
+        let input = "{\n  \"antithesis.duration\": \"30\",\n  \"antithesis.description\" \"x\"\n}";
+        let err = Params::from_json5(input).unwrap_err().to_string();
+        assert_eq!(
+            err,
+            "invalid arguments: invalid JSON at line 3, column 28: expected colon\n  \
+             3 |   \"antithesis.description\" \"x\"\n    |                            ^"
+        );
+    }
+
+    #[test]
+    fn from_json5_errors_after_json5_syntax() {
+        // A comment, unquoted key, and trailing comma are all valid JSON5, so
+        // the error is on line 5
+        let input =
+            "{\n  // nightly\n  duration: '30',\n  tags: ['a', 'b',],\n  images: [1,,2],\n}";
+        let err = Params::from_json5(input).unwrap_err().to_string();
+        assert!(
+            err.contains("invalid JSON at line 5, column 14: "),
+            "{}",
+            err
+        );
+        assert!(
+            err.ends_with("\n  5 |   images: [1,,2],\n    |              ^"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn fingerprint_ignores_key_order() {
+        let a = Params::from_args([
+            "--antithesis.duration",
+            "30",
+            "--antithesis.description",
+            "x",
+        ])
+        .unwrap();
+        let b = Params::from_json(
+            &serde_json::json!({ "antithesis.description": "x", "antithesis.duration": "30" }),
+        )
+        .unwrap();
+        assert_eq!(a.fingerprint(), b.fingerprint());
+        assert_eq!(a.fingerprint().len(), 8);
+
+        let nested = |value: serde_json::Value| {
+            Params::from_json(&serde_json::json!({ "mycorp.limits": value }))
+                .unwrap()
+                .fingerprint()
+        };
+        assert_eq!(
+            nested(serde_json::json!({ "cpu": 2, "mem": 4 })),
+            nested(serde_json::json!({ "mem": 4, "cpu": 2 }))
+        );
+
+        // The first 8 hex digits of the SHA-256 of `key\0value\0` pairs
+        let params = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        assert_eq!(params.fingerprint(), "90853dc5");
+
+        let c = Params::from_args([
+            "--antithesis.duration",
+            "31",
+            "--antithesis.description",
+            "x",
+        ])
+        .unwrap();
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_keys() {
+        let old = Params::from_args([
+            "--antithesis.duration",
+            "30",
+            "--antithesis.description",
+            "nightly",
+            "--antithesis.integrations.slack.token",
+            "old-secret",
+        ])
+        .unwrap();
+        let new = Params::from_args([
+            "--antithesis.duration",
+            "60",
+            "--antithesis.source",
+            "main",
+            "--antithesis.integrations.slack.token",
+            "new-secret",
+        ])
+        .unwrap();
+
+        let changes: Vec<String> = old.diff(&new).iter().map(ToString::to_string).collect();
+        assert_eq!(
+            changes,
+            [
+                r#"- antithesis.description: "nightly""#,
+                r#"~ antithesis.duration: "30" -> "60""#,
+                r#"~ antithesis.integrations.slack.token: "[REDACTED]" -> "[REDACTED]""#,
+                r#"+ antithesis.source: "main""#,
+            ]
+        );
+        assert!(old.diff(&old).is_empty());
+    }
+
+    #[test]
+    fn validate_test_params_with_labels() {
+        let mut params = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        params.insert("antithesis.labels.ci_job", "1234");
+        assert!(params.validate_test_params().is_ok());
+
+        params.insert("antithesis.labels.not-an-ident", "x");
+        assert!(params.validate_test_params().is_err());
+    }
+
+    #[test]
+    fn parse_inline_values() {
+        let params = Params::from_args([
+            "--antithesis.duration=30",
+            "--antithesis.description=",
+            "--antithesis.test_name",
+            "a=b",
+        ])
+        .unwrap();
+        let map = params.as_map();
+
+        assert_eq!(map.get("antithesis.duration").unwrap(), "30");
+        assert_eq!(map.get("antithesis.description").unwrap(), "");
+        assert_eq!(map.get("antithesis.test_name").unwrap(), "a=b");
+        assert!(Params::from_args(["--=30"]).is_err());
+    }
+
+    #[test]
+    fn hyphen_values_allowed_by_default() {
+        let params = Params::from_args(["--antithesis.duration", "-30"]).unwrap();
+        assert_eq!(params.as_map().get("antithesis.duration").unwrap(), "-30");
+    }
+
+    #[test]
+    fn strict_rejects_hyphen_values() {
+        let strict = ArgsOptions {
+            strict: true,
+            ..ArgsOptions::default()
+        };
+
+        let err = Params::from_args_with(["--antithesis.duration", "-30"], &strict).unwrap_err();
+        assert!(err.to_string().contains(r#"suspicious value "-30""#));
+
+        let err = Params::from_args_with(
+            ["--antithesis.images", "--antithesis.duration", "30"],
+            &strict,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("looks like a flag"));
+    }
+
+    #[test]
+    fn prefix_applies_to_keys_without_a_dot() {
+        let options = ArgsOptions {
+            prefix: Some("my.ns".to_string()),
+            ..ArgsOptions::default()
+        };
+        let params = Params::from_args_with(
+            [
+                "--foo",
+                "bar",
+                "--antithesis.duration",
+                "30",
+                "--other.ns.key=v",
+            ],
+            &options,
+        )
+        .unwrap();
+        let map = params.as_map();
+        assert_eq!(map["my.ns.foo"], "bar");
+        assert_eq!(map["antithesis.duration"], "30");
+        assert_eq!(map["other.ns.key"], "v");
+        assert_eq!(map.len(), 3);
+    }
+
+    #[test]
+    fn validate_with_custom_schema() {
+        let schema = Schema::from_json(serde_json::json!({
+            "$defs": {
+                "testParams": { "type": "object", "required": ["my.required"] },
+                "debuggingParams": { "type": "object" }
+            }
+        }));
+        assert!(schema.check().is_ok());
+
+        let params = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        assert!(params.validate_test_params().is_ok());
+        let err = params
+            .validate_test_params_with(&schema, &ValidateOptions::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("my.required"));
+    }
+
+    #[test]
+    fn schema_check_requires_both_defs() {
+        let schema = Schema::from_json(serde_json::json!({
+            "$defs": { "testParams": { "type": "object" } }
+        }));
+        assert_eq!(schema.check().unwrap_err(), "missing $defs/debuggingParams");
+        assert!(Schema::bundled().unwrap().check().is_ok());
+    }
+
+    #[test]
+    fn humanize_duration_converts_to_minutes() {
+        for (input, expected) in [("30m", "30"), ("1h", "60"), ("1h 30m", "90"), ("90", "90")] {
+            let mut params = Params::from_args(["--antithesis.duration", input]).unwrap();
+            params.humanize_duration().unwrap();
+            assert_eq!(
+                params.as_map().get("antithesis.duration").unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn humanize_duration_rejects_partial_minutes() {
+        let mut params = Params::from_args(["--antithesis.duration", "90s"]).unwrap();
+        let err = params.humanize_duration().unwrap_err();
+        assert!(err.to_string().contains("not a whole number of minutes"));
+
+        let mut params = Params::from_args(["--antithesis.duration", "soon"]).unwrap();
+        assert!(params.humanize_duration().is_err());
+    }
+
     #[test]
     fn missing_value_error() {
         let args = ["--antithesis.duration"];
@@ -319,6 +1855,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn merge_tracks_sources() {
+        let mut config = Params::default();
+        config.insert("antithesis.source", "ci");
+        config.insert("antithesis.description", "from config");
+        let mut params = config.with_source(Source::Config);
+
+        let stdin = Params::from_json(&serde_json::json!({
+            "antithesis.description": "from stdin",
+            "antithesis.report.recipients": "team@example.com",
+        }))
+        .unwrap();
+        params.merge(stdin.with_source(Source::Stdin));
+
+        let cli = Params::from_args(["--antithesis.duration", "30"]).unwrap();
+        params.merge(cli.with_source(Source::Cli));
+        params.insert_from("antithesis.images", "app:1", Source::File);
+
+        assert_eq!(params.source("antithesis.source"), Some(Source::Config));
+        assert_eq!(params.source("antithesis.description"), Some(Source::Stdin));
+        assert_eq!(params.source("antithesis.duration"), Some(Source::Cli));
+        assert_eq!(params.source("antithesis.images"), Some(Source::File));
+
+        // A merge without sources forgets the overwritten key's old source
+        params.merge(Params::from_args(["--antithesis.source", "main"]).unwrap());
+        assert_eq!(params.source("antithesis.source"), None);
+
+        let sourced = params.to_sourced_map();
+        assert_eq!(
+            sourced["antithesis.report.recipients"],
+            serde_json::json!({ "value": "[REDACTED]", "source": "stdin" })
+        );
+        assert_eq!(
+            sourced["antithesis.source"],
+            serde_json::json!({ "value": "main", "source": null })
+        );
+    }
+
     #[test]
     fn redacted_map_hides_sensitive_values() {
         let args = [
@@ -355,4 +1929,51 @@ mod tests {
             "[REDACTED]"
         );
     }
+
+    #[test]
+    fn redaction_is_display_only() {
+        let params = Params::from_args([
+            "--antithesis.integrations.github.token",
+            "secret_token_123",
+            "--antithesis.report.recipients",
+            "user@example.com",
+        ])
+        .unwrap();
+
+        // What's sent keeps the real values
+        let sent = params.to_value();
+        assert_eq!(
+            sent["antithesis.integrations.github.token"],
+            "secret_token_123"
+        );
+        assert_eq!(sent["antithesis.report.recipients"], "user@example.com");
+
+        // What's shown doesn't
+        let shown = serde_json::to_string(&params.to_redacted_map()).unwrap();
+        assert!(!shown.contains("secret_token_123"));
+        assert!(!shown.contains("user@example.com"));
+    }
+
+    #[test]
+    fn leaked_keys_finds_secrets_under_other_keys() {
+        let params = Params::from_args([
+            "--antithesis.integrations.github.token",
+            "secret_token_123",
+            "--antithesis.report.recipients",
+            "user@example.com;other@example.com",
+            "--antithesis.description",
+            "cc other@example.com",
+            "--antithesis.test_name",
+            "uses hunter2",
+            "--antithesis.duration",
+            "30",
+        ])
+        .unwrap();
+
+        assert_eq!(
+            params.leaked_keys(&["hunter2".to_string()]),
+            ["antithesis.description", "antithesis.test_name"]
+        );
+        assert_eq!(params.leaked_keys(&[]), ["antithesis.description"]);
+    }
 }
@@ -0,0 +1,198 @@
+//! Checks for `antithesis.report.recipients` and its per-channel variants,
+//! e.g. `antithesis.report.recipients.slack`.
+
+use std::collections::BTreeSet;
+use std::future::Future;
+
+use hickory_resolver::TokioResolver;
+use log::debug;
+use url::Url;
+
+/// The flat recipients key, a semicolon-delimited list of email addresses.
+pub const RECIPIENTS_KEY: &str = "antithesis.report.recipients";
+
+/// The channel of a per-channel recipients key, e.g. `slack` for
+/// `antithesis.report.recipients.slack`.
+pub fn channel(key: &str) -> Option<&str> {
+    key.strip_prefix(RECIPIENTS_KEY)?
+        .strip_prefix('.')
+        .filter(|channel| !channel.is_empty())
+}
+
+/// Check each entry of a channel's semicolon-delimited recipients: email
+/// addresses for `email`, http(s) URLs for every other channel. Returns one
+/// error per bad entry.
+pub fn validate_channel(channel: &str, recipients: &str) -> Vec<String> {
+    let key = format!("{}.{}", RECIPIENTS_KEY, channel);
+    recipients
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let problem = if channel == "email" {
+                (!is_email(entry)).then_some("is not an email address")
+            } else {
+                (!is_http_url(entry)).then_some("is not an http(s) URL")
+            };
+            problem.map(|problem| format!("{}: {:?} {}", key, entry, problem))
+        })
+        .collect()
+}
+
+fn is_email(entry: &str) -> bool {
+    match entry.rsplit_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !entry.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+fn is_http_url(entry: &str) -> bool {
+    Url::parse(entry)
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some())
+}
+
+/// Looks up whether a domain has MX records.
+pub trait MxLookup {
+    /// Returns `Ok(true)` if the domain has MX records, `Ok(false)` if it has
+    /// none, and `Err` with a description if the lookup itself failed.
+    fn has_mx(&self, domain: &str) -> impl Future<Output = Result<bool, String>>;
+}
+
+/// [`MxLookup`] backed by the system DNS configuration.
+pub struct DnsMxLookup {
+    resolver: TokioResolver,
+}
+
+impl DnsMxLookup {
+    pub fn from_system_conf() -> Result<Self, String> {
+        let resolver = TokioResolver::builder_tokio()
+            .map_err(|e| e.to_string())?
+            .build();
+        Ok(Self { resolver })
+    }
+}
+
+impl MxLookup for DnsMxLookup {
+    async fn has_mx(&self, domain: &str) -> Result<bool, String> {
+        match self.resolver.mx_lookup(domain).await {
+            Ok(lookup) => Ok(lookup.iter().next().is_some()),
+            Err(e) if e.is_no_records_found() => Ok(false),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Check that each recipient's domain can receive email.
+///
+/// `recipients` is a semicolon-delimited list of addresses, from
+/// `antithesis.report.recipients` or its `email` channel. Returns a warning
+/// for every domain without MX records or whose lookup failed; problems never
+/// abort the launch.
+pub async fn check_recipients(recipients: &str, resolver: &impl MxLookup) -> Vec<String> {
+    let domains: BTreeSet<&str> = recipients
+        .split(';')
+        .filter_map(|r| r.trim().rsplit_once('@'))
+        .map(|(_, domain)| domain)
+        .filter(|domain| !domain.is_empty())
+        .collect();
+
+    let mut warnings = Vec::new();
+    for domain in domains {
+        debug!("looking up MX records for {}", domain);
+        match resolver.has_mx(domain).await {
+            Ok(true) => {}
+            Ok(false) => warnings.push(format!(
+                "no MX records for {}; reports to this domain are likely undeliverable",
+                domain
+            )),
+            Err(e) => warnings.push(format!(
+                "could not look up MX records for {}: {}",
+                domain, e
+            )),
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeLookup;
+
+    impl MxLookup for FakeLookup {
+        async fn has_mx(&self, domain: &str) -> Result<bool, String> {
+            match domain {
+                "example.com" => Ok(true),
+                "no-mail.example" => Ok(false),
+                _ => Err("timed out".to_string()),
+            }
+        }
+    }
+
+    #[test]
+    fn parses_channel_keys() {
+        assert_eq!(channel("antithesis.report.recipients.slack"), Some("slack"));
+        assert_eq!(channel("antithesis.report.recipients"), None);
+        assert_eq!(channel("antithesis.report.recipients."), None);
+        assert_eq!(channel("antithesis.report.recipientsx"), None);
+    }
+
+    #[test]
+    fn validates_email_channel() {
+        assert!(validate_channel("email", "team@example.com; ops@example.org;").is_empty());
+        assert_eq!(
+            validate_channel("email", "team@example.com;ops;dev@localhost"),
+            [
+                r#"antithesis.report.recipients.email: "ops" is not an email address"#,
+                r#"antithesis.report.recipients.email: "dev@localhost" is not an email address"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn validates_slack_channel_urls() {
+        assert!(validate_channel("slack", "https://hooks.slack.com/services/T0/B0/xyz").is_empty());
+        assert_eq!(
+            validate_channel("slack", "team@example.com;ftp://example.com/hook"),
+            [
+                r#"antithesis.report.recipients.slack: "team@example.com" is not an http(s) URL"#,
+                r#"antithesis.report.recipients.slack: "ftp://example.com/hook" is not an http(s) URL"#,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn warns_for_domains_without_mx() {
+        let warnings = check_recipients(
+            "team@example.com; ops@no-mail.example;other@example.com",
+            &FakeLookup,
+        )
+        .await;
+
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("no MX records for no-mail.example"));
+    }
+
+    #[tokio::test]
+    async fn warns_when_lookup_fails() {
+        let warnings = check_recipients("team@unreachable.example", &FakeLookup).await;
+
+        assert_eq!(
+            warnings,
+            ["could not look up MX records for unreachable.example: timed out"]
+        );
+    }
+
+    #[tokio::test]
+    async fn ignores_entries_without_domain() {
+        let warnings = check_recipients("not-an-email;;", &FakeLookup).await;
+        assert!(warnings.is_empty());
+    }
+}
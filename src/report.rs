@@ -0,0 +1,142 @@
+//! Debugging params from a triage report.
+
+use log::debug;
+use reqwest::Method;
+use serde_json::Value;
+
+use crate::api::AntithesisApi;
+use crate::error::{Error, Result};
+use crate::params::DebuggingParams;
+
+/// Fetch a triage report as JSON and take the moment to debug from it.
+///
+/// URLs starting with `/` are relative to the API base URL; credentials are
+/// only sent to the API base URL's origin. The report's
+/// `session_id`, `input_hash`, and `vtime` are read from its top level, or
+/// from a `moment` object.
+pub async fn debugging_params(api: &AntithesisApi, url: &str) -> Result<DebuggingParams> {
+    let request = if url.starts_with('/') {
+        api.get(url)
+    } else {
+        api.get_url(url)
+    };
+    let response = request.header("Accept", "application/json").send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(Error::Api {
+            status: status.as_u16(),
+            method: Method::GET,
+            path: response.url().path().to_string(),
+            message: response.text().await?,
+        });
+    }
+
+    let body = response.text().await?;
+    let report: Value = serde_json::from_str(&body)
+        .map_err(|e| Error::UnexpectedResponse(format!("report is not valid JSON: {}", e)))?;
+    let moment = match report.get("moment") {
+        Some(moment) if moment.is_object() => moment,
+        _ => &report,
+    };
+    let field = |name: &str| match moment.get(name) {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Number(n)) => n.to_string(),
+        _ => String::new(),
+    };
+    debug!("read debugging moment from report {}", url);
+
+    DebuggingParams::builder()
+        .session_id(field("session_id"))
+        .input_hash(field("input_hash"))
+        .vtime(field("vtime"))
+        .build()
+        .map_err(|e| match e {
+            Error::InvalidArgs(message) => {
+                Error::UnexpectedResponse(format!("report {}: {}", url, message))
+            }
+            other => other,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Config;
+    use crate::params::Params;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn api(server: &MockServer) -> AntithesisApi {
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        AntithesisApi::with_base_url(config, server.uri()).unwrap()
+    }
+
+    async fn serve(report: &str) -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/report/abc"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(report))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn reads_moment_from_report() {
+        let server = serve(
+            r#"{"title": "triage", "moment": {"session_id": "s-1", "input_hash": "-42", "vtime": 329.8}}"#,
+        )
+        .await;
+
+        let params = Params::from(
+            debugging_params(&api(&server), "/report/abc")
+                .await
+                .unwrap(),
+        );
+        let map = params.as_map();
+        assert_eq!(map["antithesis.debugging.session_id"], "s-1");
+        assert_eq!(map["antithesis.debugging.input_hash"], "-42");
+        assert_eq!(map["antithesis.debugging.vtime"], "329.8");
+    }
+
+    #[tokio::test]
+    async fn sends_no_credentials_to_other_hosts() {
+        let report_server =
+            serve(r#"{"session_id": "s-1", "input_hash": "-42", "vtime": 329.8}"#).await;
+        let api_server = MockServer::start().await;
+
+        debugging_params(
+            &api(&api_server),
+            &format!("{}/report/abc", report_server.uri()),
+        )
+        .await
+        .unwrap();
+
+        let requests = report_server.received_requests().await.unwrap();
+        assert!(!requests[0].headers.contains_key("authorization"));
+    }
+
+    #[tokio::test]
+    async fn rejects_report_without_moment() {
+        let server = serve(r#"{"session_id": "s-1"}"#).await;
+
+        let err = debugging_params(&api(&server), "/report/abc")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("missing debugging input_hash"));
+    }
+
+    #[tokio::test]
+    async fn reports_http_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let err = debugging_params(&api(&server), "/report/abc")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("404 on GET /report/abc"));
+    }
+}
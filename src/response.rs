@@ -0,0 +1,200 @@
+//! Inspection of webhook response bodies.
+
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+/// A `key=value` pair that a successful JSON response body must contain.
+///
+/// Some webhooks return a 2xx status with a body like `{"status": "error"}`,
+/// so the status code alone isn't enough to know the launch succeeded.
+#[derive(Debug, Clone)]
+pub struct ExpectField {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for ExpectField {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=VALUE, got {:?}", s))?;
+        if key.is_empty() {
+            return Err("empty key in KEY=VALUE".to_string());
+        }
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl ExpectField {
+    /// Check that the response body is a JSON object containing the expected field.
+    ///
+    /// Non-string values are compared using their JSON representation, so
+    /// `ok=true` matches `{"ok": true}`.
+    pub fn check(&self, body: &str) -> Result<()> {
+        let value: Value = serde_json::from_str(body).map_err(|_| {
+            Error::UnexpectedResponse(format!(
+                "expected {}={} but the response is not JSON: {}",
+                self.key, self.value, body
+            ))
+        })?;
+
+        let actual = match value.get(&self.key) {
+            Some(Value::String(s)) => s.clone(),
+            Some(other) => sorted_json(other.clone()),
+            None => {
+                return Err(Error::UnexpectedResponse(format!(
+                    "expected {}={} but the response has no {} field: {}",
+                    self.key, self.value, self.key, body
+                )));
+            }
+        };
+
+        if actual != self.value {
+            return Err(Error::UnexpectedResponse(format!(
+                "expected {}={} but got {}={}: {}",
+                self.key, self.value, self.key, actual, body
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A JSON pointer (RFC 6901) to a field of the response, such as `/run_id`.
+#[derive(Debug, Clone)]
+pub struct FieldPointer(String);
+
+impl FromStr for FieldPointer {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if !s.starts_with('/') {
+            return Err(format!(
+                "expected a JSON pointer starting with /, e.g. /run_id, got {:?}",
+                s
+            ));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl FieldPointer {
+    /// Extract the field from a JSON response body. Strings are returned
+    /// without quotes; other values as JSON.
+    pub fn extract(&self, body: &str) -> Result<String> {
+        match self.select(body)? {
+            Value::String(s) => Ok(s),
+            other => Ok(other.to_string()),
+        }
+    }
+
+    /// Take the field out of a JSON response body as a JSON value.
+    pub fn select(&self, body: &str) -> Result<Value> {
+        let mut value: Value = serde_json::from_str(body).map_err(|_| {
+            Error::UnexpectedResponse(format!(
+                "expected a {} field but the response is not JSON: {}",
+                self.0, body
+            ))
+        })?;
+        let mut field = value.pointer_mut(&self.0).map(Value::take).ok_or_else(|| {
+            Error::UnexpectedResponse(format!("the response has no {} field: {}", self.0, body))
+        })?;
+        // Objects come back with sorted keys, whatever order the server used
+        field.sort_all_objects();
+        Ok(field)
+    }
+}
+
+/// `value` as compact JSON with every object's keys sorted.
+fn sorted_json(mut value: Value) -> String {
+    value.sort_all_objects();
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expect(s: &str) -> ExpectField {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_expect_field() {
+        let field = expect("status=ok");
+        assert_eq!(field.key, "status");
+        assert_eq!(field.value, "ok");
+    }
+
+    #[test]
+    fn parse_expect_field_rejects_invalid() {
+        assert!("status".parse::<ExpectField>().is_err());
+        assert!("=ok".parse::<ExpectField>().is_err());
+    }
+
+    #[test]
+    fn check_matching_field() {
+        assert!(expect("status=ok").check(r#"{"status": "ok"}"#).is_ok());
+        assert!(expect("ok=true").check(r#"{"ok": true}"#).is_ok());
+        assert!(
+            expect(r#"run={"a":1,"b":2}"#)
+                .check(r#"{"run": {"b": 2, "a": 1}}"#)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn check_mismatched_field() {
+        let err = expect("status=ok")
+            .check(r#"{"status": "error"}"#)
+            .unwrap_err();
+        assert!(err.to_string().contains("got status=error"));
+    }
+
+    #[test]
+    fn check_missing_field_or_non_json() {
+        assert!(expect("status=ok").check(r#"{"other": 1}"#).is_err());
+        assert!(expect("status=ok").check("not json").is_err());
+    }
+
+    #[test]
+    fn extract_field() {
+        let pointer: FieldPointer = "/run/id".parse().unwrap();
+        assert_eq!(pointer.extract(r#"{"run": {"id": "abc"}}"#).unwrap(), "abc");
+        assert_eq!(pointer.extract(r#"{"run": {"id": 7}}"#).unwrap(), "7");
+
+        let err = pointer.extract(r#"{"run": {}}"#).unwrap_err();
+        assert!(err.to_string().contains("no /run/id field"));
+        assert!(pointer.extract("not json").is_err());
+        assert!("run_id".parse::<FieldPointer>().is_err());
+    }
+
+    #[test]
+    fn extract_sorts_object_keys() {
+        let pointer: FieldPointer = "/run".parse().unwrap();
+        assert_eq!(
+            pointer
+                .extract(r#"{"run": {"id": "abc", "attempt": {"n": 2, "at": "now"}}}"#)
+                .unwrap(),
+            r#"{"attempt":{"at":"now","n":2},"id":"abc"}"#
+        );
+    }
+
+    #[test]
+    fn select_nested_root() {
+        let root: FieldPointer = "/data".parse().unwrap();
+        let body = r#"{"data": {"run_id": "abc"}, "meta": {"request_id": "r-1"}}"#;
+        assert_eq!(
+            root.select(body).unwrap(),
+            serde_json::json!({ "run_id": "abc" })
+        );
+        assert!(root.select(r#"{"meta": {}}"#).is_err());
+    }
+}
@@ -0,0 +1,214 @@
+//! Fetching the params schema from a URL, cached locally by ETag.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::debug;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use reqwest::{Method, StatusCode};
+use serde_json::Value;
+
+use crate::api::AntithesisApi;
+use crate::error::{Error, Result};
+use crate::params::Schema;
+
+/// The default directory for cached schemas: `$XDG_CACHE_HOME/snouty`, or
+/// `~/.cache/snouty` when `XDG_CACHE_HOME` is unset.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("snouty"))
+}
+
+/// Fetch a schema from `url` using the API client.
+///
+/// URLs starting with `/` are relative to the API base URL. Credentials are
+/// only sent to the API base URL's origin. When `cache_dir`
+/// is given, the schema and its ETag are cached there and revalidated with
+/// `If-None-Match`, so an unchanged schema isn't downloaded again.
+///
+/// A schema that isn't JSON, or whose params definitions don't compile, gives
+/// [`Error::InvalidSchema`].
+pub async fn fetch(api: &AntithesisApi, url: &str, cache_dir: Option<&Path>) -> Result<Schema> {
+    let cache = cache_dir.map(|dir| CacheEntry::new(dir, url));
+    let cached = cache.as_ref().and_then(CacheEntry::load);
+
+    let mut request = if url.starts_with('/') {
+        api.get(url)
+    } else {
+        api.get_url(url)
+    };
+    if let Some((etag, _)) = &cached {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+
+    let response = request.send().await?;
+    let status = response.status();
+
+    let body = match (status, cached) {
+        (StatusCode::NOT_MODIFIED, Some((_, body))) => {
+            debug!("schema at {} not modified, using cached copy", url);
+            body
+        }
+        (status, _) if status.is_success() => {
+            let etag = response
+                .headers()
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string);
+            let body = response.text().await?;
+            if let (Some(cache), Some(etag)) = (&cache, etag) {
+                cache.store(&etag, &body);
+            }
+            body
+        }
+        (status, _) => {
+            return Err(Error::Api {
+                status: status.as_u16(),
+                method: Method::GET,
+                path: response.url().path().to_string(),
+                message: response.text().await?,
+            });
+        }
+    };
+
+    let value: Value = serde_json::from_str(&body)
+        .map_err(|e| Error::InvalidSchema(format!("{} is not valid JSON: {}", url, e)))?;
+    let schema = Schema::from_json(value);
+    schema
+        .check()
+        .map_err(|e| Error::InvalidSchema(format!("{}: {}", url, e)))?;
+    Ok(schema)
+}
+
+/// A cached schema body and its ETag, stored side by side.
+struct CacheEntry {
+    body_path: PathBuf,
+    etag_path: PathBuf,
+}
+
+impl CacheEntry {
+    fn new(dir: &Path, url: &str) -> Self {
+        let key: String = url
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        Self {
+            body_path: dir.join(format!("{}.json", key)),
+            etag_path: dir.join(format!("{}.etag", key)),
+        }
+    }
+
+    fn load(&self) -> Option<(String, String)> {
+        let etag = fs::read_to_string(&self.etag_path).ok()?;
+        let body = fs::read_to_string(&self.body_path).ok()?;
+        Some((etag, body))
+    }
+
+    /// Caching is best effort; failures are only logged.
+    fn store(&self, etag: &str, body: &str) {
+        let result = self
+            .body_path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&self.body_path, body))
+            .and_then(|_| fs::write(&self.etag_path, etag));
+        if let Err(e) = result {
+            debug!("failed to cache schema: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Config;
+    use crate::params::ValidateOptions;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const REMOTE_SCHEMA: &str = r#"{
+        "$defs": {
+            "testParams": { "type": "object", "required": ["my.required"] },
+            "debuggingParams": { "type": "object" }
+        }
+    }"#;
+
+    fn api(server: &MockServer) -> AntithesisApi {
+        let config = Config::new("user".to_string(), "pass".to_string(), "tenant".to_string());
+        AntithesisApi::with_base_url(config, server.uri()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetch_caches_by_etag() {
+        let server = MockServer::start().await;
+        let cache_dir = tempfile::tempdir().unwrap();
+
+        Mock::given(method("GET"))
+            .and(path("/schema.json"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/schema.json"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("ETag", "\"v1\"")
+                    .set_body_string(REMOTE_SCHEMA),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let api = api(&server);
+        let url = format!("{}/schema.json", server.uri());
+        let params = crate::params::Params::from_args(["--antithesis.duration", "30"]).unwrap();
+
+        for _ in 0..2 {
+            let schema = fetch(&api, &url, Some(cache_dir.path())).await.unwrap();
+            let err = params
+                .validate_test_params_with(&schema, &ValidateOptions::default())
+                .unwrap_err();
+            assert!(err.to_string().contains("my.required"));
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_rejects_unusable_schema() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/schema.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(r#"{"$defs": {}}"#))
+            .mount(&server)
+            .await;
+
+        let err = fetch(&api(&server), "/schema.json", None)
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("missing $defs/testParams"));
+    }
+
+    #[tokio::test]
+    async fn fetch_reports_http_errors() {
+        let server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let err = fetch(&api(&server), "/schema.json", None)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Api { status: 404, .. }));
+        assert!(err.to_string().contains("404 on GET /schema.json"));
+    }
+}
@@ -0,0 +1,271 @@
+//! The optional snouty config file.
+//!
+//! The config file is JSON5 and lives at `$SNOUTY_CONFIG`, or by default at
+//! `$XDG_CONFIG_HOME/snouty/config.json5` (`~/.config/snouty/config.json5`).
+//! A missing default config file is the same as an empty one.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use log::debug;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::api::EnvDefaults;
+use crate::error::{Error, Result};
+use crate::params::{Params, Schema, Source};
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    /// Params to set from environment variables, mapping each variable name
+    /// to the param key it fills (e.g. `CI_COMMIT_SHA` to
+    /// `antithesis.labels.commit`). Unset variables are skipped.
+    pub env_params: BTreeMap<String, String>,
+
+    /// Webhook `snouty run` uses when `--webhook` is omitted.
+    pub default_webhook: Option<String>,
+
+    /// Path launch requests are sent to. `{webhook}` is replaced with the
+    /// webhook name, which is `debugging` for `snouty debug`.
+    pub launch_path: Option<String>,
+
+    /// A JSON schema file for a team's own (non-`antithesis.`) params. Its
+    /// `testParams` and `debuggingParams` definitions are checked in addition
+    /// to the base schema's.
+    pub supplemental_schema: Option<PathBuf>,
+
+    /// Tenants (e.g. production) that launches only go to with
+    /// `--confirm-prod`.
+    pub protected_tenants: Vec<String>,
+
+    /// Params every launch must set, e.g. `antithesis.report.recipients`.
+    /// `--require-field` adds to these.
+    pub required_fields: Vec<String>,
+
+    /// Minutes `snouty run` adds to the run's duration when estimating when
+    /// the report email arrives. `--email-buffer-mins` takes priority.
+    pub email_buffer_mins: Option<u32>,
+
+    /// Named sets of tenant and base URL defaults, selected with `--profile`
+    /// or `$SNOUTY_ENV` (e.g. `staging`).
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Defaults for `ANTITHESIS_TENANT` and `ANTITHESIS_BASE_URL`. The
+/// environment variables win when they're set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub tenant: Option<String>,
+    pub base_url: Option<String>,
+}
+
+const DEFAULT_LAUNCH_PATH: &str = "/launch/{webhook}";
+
+impl Settings {
+    /// Load the config file, if there is one.
+    pub fn load() -> Result<Self> {
+        let (path, explicit) = match env::var_os("SNOUTY_CONFIG") {
+            Some(path) => (PathBuf::from(path), true),
+            None => match default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == ErrorKind::NotFound && !explicit => {
+                debug!("no config file at {}", path.display());
+                return Ok(Self::default());
+            }
+            Err(e) => {
+                return Err(Error::Config {
+                    path,
+                    message: e.to_string(),
+                });
+            }
+        };
+
+        debug!("loading config from {}", path.display());
+        json5::from_str(&contents).map_err(|e| Error::Config {
+            path,
+            message: e.to_string(),
+        })
+    }
+
+    /// Add the `supplemental_schema`, if one is configured, to `schema`.
+    pub fn supplement_schema(&self, schema: Schema) -> Result<Schema> {
+        let Some(path) = &self.supplemental_schema else {
+            return Ok(schema);
+        };
+        let contents = fs::read_to_string(path).map_err(|e| {
+            Error::InvalidSchema(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        let supplement: Value = serde_json::from_str(&contents).map_err(|e| {
+            Error::InvalidSchema(format!("{} is not valid JSON: {}", path.display(), e))
+        })?;
+        debug!("using supplemental schema {}", path.display());
+        schema.with_supplement(&supplement)
+    }
+
+    /// The tenant and base URL defaults from the profile `name`, or none
+    /// without a profile.
+    pub fn env_defaults(&self, name: Option<&str>) -> Result<EnvDefaults> {
+        let Some(name) = name else {
+            return Ok(EnvDefaults::default());
+        };
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let known: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            Error::InvalidArgs(format!(
+                "unknown profile {:?} (defined profiles: {})",
+                name,
+                if known.is_empty() {
+                    "none".to_string()
+                } else {
+                    known.join(", ")
+                }
+            ))
+        })?;
+        debug!("using profile {}", name);
+        Ok(EnvDefaults {
+            tenant: profile.tenant.clone(),
+            base_url: profile.base_url.clone(),
+        })
+    }
+
+    /// Build params from the `env_params` mapping using the process environment.
+    pub fn env_params(&self) -> Params {
+        self.env_params_from(|name| env::var(name).ok())
+    }
+
+    /// The launch request path for `webhook`.
+    ///
+    /// `$SNOUTY_LAUNCH_PATH` takes priority over `launch_path` in the config file.
+    pub fn launch_path(&self, webhook: &str) -> String {
+        self.launch_path_from(env::var("SNOUTY_LAUNCH_PATH").ok(), webhook)
+    }
+
+    fn launch_path_from(&self, env_template: Option<String>, webhook: &str) -> String {
+        let template = env_template
+            .filter(|t| !t.is_empty())
+            .or_else(|| self.launch_path.clone())
+            .unwrap_or_else(|| DEFAULT_LAUNCH_PATH.to_string());
+        template.replace("{webhook}", webhook)
+    }
+
+    fn env_params_from(&self, lookup: impl Fn(&str) -> Option<String>) -> Params {
+        let mut params = Params::default();
+        for (var, key) in &self.env_params {
+            match lookup(var) {
+                Some(value) => {
+                    debug!("setting {} from ${}", key, var);
+                    params.insert(key.clone(), value);
+                }
+                None => debug!("skipping {}: ${} is not set", key, var),
+            }
+        }
+        params.with_source(Source::Config)
+    }
+}
+
+fn default_path() -> Option<PathBuf> {
+    let base = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    Some(base.join("snouty").join("config.json5"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_env_params() {
+        let settings: Settings =
+            json5::from_str(r#"{ env_params: { CI_COMMIT_SHA: "antithesis.labels.commit" } }"#)
+                .unwrap();
+        assert_eq!(
+            settings.env_params.get("CI_COMMIT_SHA").unwrap(),
+            "antithesis.labels.commit"
+        );
+    }
+
+    #[test]
+    fn selects_profile() {
+        let settings: Settings = json5::from_str(
+            r#"{ profiles: {
+                staging: { tenant: "acme-staging" },
+                prod: { tenant: "acme", base_url: "https://acme.example.com/api/v1" },
+            } }"#,
+        )
+        .unwrap();
+
+        let staging = settings.env_defaults(Some("staging")).unwrap();
+        assert_eq!(staging.tenant.as_deref(), Some("acme-staging"));
+        assert_eq!(staging.base_url, None);
+        let prod = settings.env_defaults(Some("prod")).unwrap();
+        assert_eq!(
+            prod.base_url.as_deref(),
+            Some("https://acme.example.com/api/v1")
+        );
+        assert_eq!(settings.env_defaults(None).unwrap().tenant, None);
+
+        let err = settings.env_defaults(Some("dev")).unwrap_err();
+        assert!(err.to_string().contains("defined profiles: prod, staging"));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(json5::from_str::<Settings>(r#"{ unknown: 1 }"#).is_err());
+    }
+
+    #[test]
+    fn default_launch_path() {
+        let settings = Settings::default();
+        assert_eq!(
+            settings.launch_path_from(None, "basic_test"),
+            "/launch/basic_test"
+        );
+        assert_eq!(
+            settings.launch_path_from(None, "debugging"),
+            "/launch/debugging"
+        );
+    }
+
+    #[test]
+    fn custom_launch_path() {
+        let settings: Settings =
+            json5::from_str(r#"{ launch_path: "/tenants/acme/launch/{webhook}" }"#).unwrap();
+        assert_eq!(
+            settings.launch_path_from(None, "basic_test"),
+            "/tenants/acme/launch/basic_test"
+        );
+        assert_eq!(
+            settings.launch_path_from(Some("/v2/{webhook}/launch".to_string()), "basic_test"),
+            "/v2/basic_test/launch"
+        );
+    }
+
+    #[test]
+    fn env_params_skip_unset_vars() {
+        let settings: Settings = json5::from_str(
+            r#"{ env_params: { CI_COMMIT_SHA: "antithesis.labels.commit", GITHUB_RUN_ID: "antithesis.labels.run" } }"#,
+        )
+        .unwrap();
+
+        let params = settings
+            .env_params_from(|name| (name == "CI_COMMIT_SHA").then(|| "abc123".to_string()));
+
+        assert_eq!(
+            params.as_map().get("antithesis.labels.commit").unwrap(),
+            "abc123"
+        );
+        assert!(!params.as_map().contains_key("antithesis.labels.run"));
+    }
+}
@@ -0,0 +1,127 @@
+//! `--template` success lines, e.g. `{webhook}: launched, eta={eta}`.
+//!
+//! Placeholders are `{name}`; write `{{` or `}}` for a literal brace.
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+
+enum Piece<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn parse(template: &str) -> Result<Vec<Piece<'_>>> {
+    let invalid = |reason: &str| Error::InvalidArgs(format!("invalid --template: {}", reason));
+    let mut pieces = Vec::new();
+    let mut rest = template;
+    while let Some(i) = rest.find(['{', '}']) {
+        if i > 0 {
+            pieces.push(Piece::Text(&rest[..i]));
+        }
+        let after = &rest[i + 1..];
+        match (&rest[i..i + 1], after.chars().next()) {
+            ("{", Some('{')) => {
+                pieces.push(Piece::Text("{"));
+                rest = &after[1..];
+            }
+            ("}", Some('}')) => {
+                pieces.push(Piece::Text("}"));
+                rest = &after[1..];
+            }
+            ("{", _) => {
+                let end = after.find('}').ok_or_else(|| invalid("unclosed {"))?;
+                pieces.push(Piece::Placeholder(&after[..end]));
+                rest = &after[end + 1..];
+            }
+            _ => return Err(invalid("unmatched }")),
+        }
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Text(rest));
+    }
+    Ok(pieces)
+}
+
+/// Check that a template parses and only uses placeholders `known` accepts,
+/// so a typo fails before anything is launched.
+pub fn check(template: &str, known: impl Fn(&str) -> bool) -> Result<()> {
+    for piece in parse(template)? {
+        if let Piece::Placeholder(name) = piece
+            && !known(name)
+        {
+            return Err(unknown(name));
+        }
+    }
+    Ok(())
+}
+
+/// Render a template, filling each placeholder from `lookup`.
+pub fn render(template: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String> {
+    let mut out = String::new();
+    for piece in parse(template)? {
+        match piece {
+            Piece::Text(text) => out.push_str(text),
+            Piece::Placeholder(name) => out.push_str(&lookup(name).ok_or_else(|| unknown(name))?),
+        }
+    }
+    Ok(out)
+}
+
+/// Look up a dotted path like `run.id` in a JSON value. Strings are returned
+/// without quotes; other values as JSON.
+pub fn lookup_path(value: &Value, path: &str) -> Option<String> {
+    let found = path
+        .split('.')
+        .try_fold(value, |value, key| value.get(key))?;
+    Some(match found {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    })
+}
+
+fn unknown(name: &str) -> Error {
+    Error::InvalidArgs(format!("unknown --template placeholder {{{}}}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "webhook" => Some("basic_test".to_string()),
+            "eta" => Some("Oct 16 at 3:04 PM".to_string()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn renders_placeholders() {
+        assert_eq!(
+            render("{webhook}: launched, eta={eta}", lookup).unwrap(),
+            "basic_test: launched, eta=Oct 16 at 3:04 PM"
+        );
+        assert_eq!(render("{{webhook}}", lookup).unwrap(), "{webhook}");
+    }
+
+    #[test]
+    fn rejects_unknown_placeholders() {
+        let err = render("{webhook} {nope}", lookup).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("unknown --template placeholder {nope}")
+        );
+        assert!(check("{nope}", |name| lookup(name).is_some()).is_err());
+        assert!(check("{webhook", |_| true).is_err());
+        assert!(check("webhook}", |_| true).is_err());
+    }
+
+    #[test]
+    fn looks_up_dotted_paths() {
+        let value = serde_json::json!({ "run": { "id": "abc", "attempt": 2 } });
+        assert_eq!(lookup_path(&value, "run.id").unwrap(), "abc");
+        assert_eq!(lookup_path(&value, "run.attempt").unwrap(), "2");
+        assert_eq!(lookup_path(&value, "run.missing"), None);
+    }
+}
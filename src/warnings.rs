@@ -0,0 +1,23 @@
+//! User-facing warnings, which `--strict-warnings` turns into errors.
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::error::{Error, Result};
+
+static STRICT: AtomicBool = AtomicBool::new(false);
+
+/// Make every later [`warn`] fail instead of printing.
+pub fn set_strict(strict: bool) {
+    STRICT.store(strict, Ordering::Relaxed);
+}
+
+/// Print a warning to stderr, or return it as an error with
+/// `--strict-warnings`.
+pub fn warn(message: impl Display) -> Result<()> {
+    if STRICT.load(Ordering::Relaxed) {
+        return Err(Error::StrictWarning(message.to_string()));
+    }
+    eprintln!("warning: {}", message);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+//! Watching a params file for `run --watch-file`.
+
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::Duration;
+
+use log::debug;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+use crate::error::{Error, Result};
+
+/// How long the file has to stay quiet before a change counts, so an editor
+/// writing in several steps only triggers one relaunch.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Reports changes to one file.
+pub struct FileWatcher {
+    // Dropping the watcher stops the events
+    _watcher: RecommendedWatcher,
+    changes: UnboundedReceiver<()>,
+}
+
+impl FileWatcher {
+    /// Start watching `path`.
+    ///
+    /// The file's directory is watched rather than the file itself, so editors
+    /// that save by replacing the file are still seen.
+    pub fn new(path: &Path) -> Result<Self> {
+        let watch_error = |e: notify::Error| {
+            Error::InvalidArgs(format!("failed to watch {}: {}", path.display(), e))
+        };
+        let name: OsString = path
+            .file_name()
+            .ok_or_else(|| Error::InvalidArgs(format!("{} is not a file", path.display())))?
+            .to_owned();
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+
+        let (sender, changes) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            let Ok(event) = event else { return };
+            let touches_file = event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == Some(name.as_os_str()));
+            if touches_file && (event.kind.is_modify() || event.kind.is_create()) {
+                let _ = sender.send(());
+            }
+        })
+        .map_err(watch_error)?;
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(watch_error)?;
+        debug!("watching {} for changes", path.display());
+
+        Ok(Self {
+            _watcher: watcher,
+            changes,
+        })
+    }
+
+    /// Wait for the file to change, then for the change to settle.
+    pub async fn changed(&mut self) {
+        if self.changes.recv().await.is_none() {
+            // The watcher is gone, so no change will ever come
+            std::future::pending::<()>().await;
+        }
+        while let Ok(Some(())) = tokio::time::timeout(DEBOUNCE, self.changes.recv()).await {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[tokio::test]
+    async fn reports_a_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("params.json");
+        fs::write(&path, "{}").unwrap();
+        let mut watcher = FileWatcher::new(&path).unwrap();
+
+        fs::write(&path, r#"{"antithesis.duration": "30"}"#).unwrap();
+        tokio::time::timeout(Duration::from_secs(10), watcher.changed())
+            .await
+            .expect("change was not reported");
+    }
+}
@@ -3,46 +3,162 @@ use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 use std::io::Write;
 use std::net::TcpListener;
+use std::sync::mpsc;
 use std::thread;
 
 fn snouty() -> Command {
-    let mut cmd = cargo_bin_cmd!("snouty");
-    cmd.env("RUST_LOG", "debug");
+    with_test_env(cargo_bin_cmd!("snouty"))
+}
+
+fn with_test_env(mut cmd: Command) -> Command {
+    cmd.env("RUST_LOG", "debug")
+        // Don't pick up the config file of whoever runs the tests
+        .env_remove("SNOUTY_CONFIG")
+        .env_remove("SNOUTY_LAUNCH_PATH")
+        .env_remove("SNOUTY_LOG_FORMAT")
+        .env_remove("SNOUTY_ENV")
+        .env_remove("ANTITHESIS_MAX_BODY_BYTES")
+        .env_remove("ANTITHESIS_CONNECT_TIMEOUT")
+        .env_remove("ANTITHESIS_RETRY_BASE_DELAY")
+        .env_remove("ANTITHESIS_RETRY_MAX_DELAY")
+        .env_remove("ANTITHESIS_RETRY_JITTER")
+        .env_remove("ANTITHESIS_SIGNING_KEY")
+        .env_remove("ANTITHESIS_SIGNATURE_HEADER")
+        .env_remove("ANTITHESIS_CA_BUNDLE")
+        // Nor their ~/.netrc credentials
+        .env(
+            "NETRC",
+            concat!(env!("CARGO_TARGET_TMPDIR"), "/no-such-netrc"),
+        )
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"));
     cmd
 }
 
+/// Write a config file and return it; it's deleted when dropped.
+fn config_file(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
 /// Start a simple mock HTTP server that returns a fixed response.
 /// Returns the server URL and a handle to stop it.
 fn start_mock_server(response_body: &'static str, status: u16) -> String {
+    start_mock_server_sequence(vec![(status, response_body)])
+}
+
+/// Start a mock HTTP server that answers one request with each of the given
+/// `(status, body)` responses, in order.
+fn start_mock_server_sequence(responses: Vec<(u16, &'static str)>) -> String {
     let listener = TcpListener::bind("127.0.0.1:0").unwrap();
     let addr = listener.local_addr().unwrap();
     let url = format!("http://{}", addr);
 
     thread::spawn(move || {
-        for stream in listener.incoming() {
-            if let Ok(mut stream) = stream {
-                // Read request (we don't care about the content for these tests)
-                let mut buf = [0u8; 4096];
-                let _ = std::io::Read::read(&mut stream, &mut buf);
-
-                // Send response
-                let response = format!(
-                    "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
-                    status,
-                    response_body.len(),
-                    response_body
-                );
-                let _ = stream.write_all(response.as_bytes());
-                break; // Only handle one request
+        let mut incoming = listener.incoming().flatten();
+        for (status, response_body) in responses {
+            let Some(mut stream) = incoming.next() else {
+                break;
+            };
+
+            // Read request (we don't care about the content for these tests)
+            let mut buf = [0u8; 4096];
+            let _ = std::io::Read::read(&mut stream, &mut buf);
+
+            // Send response
+            let response = format!(
+                "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                response_body.len(),
+                response_body
+            );
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+
+    url
+}
+
+/// Start a mock HTTP server that answers each request with `{}` and sends the
+/// raw request it received over the returned channel.
+fn start_recording_mock_server() -> (String, mpsc::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let mut buf = [0u8; 4096];
+            let n = std::io::Read::read(&mut stream, &mut buf).unwrap_or(0);
+            let _ = stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+            );
+            if sender
+                .send(String::from_utf8_lossy(&buf[..n]).into_owned())
+                .is_err()
+            {
+                return;
             }
         }
     });
 
+    (url, receiver)
+}
+
+/// Start a mock HTTP server that answers one request with a chunked body and
+/// no Content-Length, sending each chunk in a separate write.
+fn start_chunked_mock_server(status: u16, chunks: &'static [&'static str]) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let url = format!("http://{}", addr);
+
+    thread::spawn(move || {
+        let Some(mut stream) = listener.incoming().flatten().next() else {
+            return;
+        };
+        let mut buf = [0u8; 4096];
+        let _ = std::io::Read::read(&mut stream, &mut buf);
+
+        let head = format!(
+            "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+            status
+        );
+        let _ = stream.write_all(head.as_bytes());
+        for chunk in chunks {
+            let _ = stream.write_all(format!("{:x}\r\n{}\r\n", chunk.len(), chunk).as_bytes());
+            let _ = stream.flush();
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let _ = stream.write_all(b"0\r\n\r\n");
+    });
+
+    url
+}
+
+/// Start a mock HTTP server that answers one request with `response`, written
+/// as-is, status line and headers included.
+fn start_raw_mock_server(response: &'static str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let url = format!("http://{}", addr);
+
+    thread::spawn(move || {
+        let Some(mut stream) = listener.incoming().flatten().next() else {
+            return;
+        };
+        let mut buf = [0u8; 4096];
+        let _ = std::io::Read::read(&mut stream, &mut buf);
+        let _ = stream.write_all(response.as_bytes());
+    });
+
     url
 }
 
 fn snouty_with_mock(mock_url: &str) -> Command {
-    let mut cmd = snouty();
+    with_mock_env(snouty(), mock_url)
+}
+
+fn with_mock_env(mut cmd: Command, mock_url: &str) -> Command {
     cmd.env("ANTITHESIS_USERNAME", "testuser")
         .env("ANTITHESIS_PASSWORD", "testpass")
         .env("ANTITHESIS_TENANT", "testtenant")
@@ -58,7 +174,8 @@ fn version_prints_version() {
         .arg("version")
         .assert()
         .success()
-        .stdout(predicate::str::is_match(r"^snouty \d+\.\d+\.\d+").unwrap());
+        .stdout(predicate::str::is_match(r"^snouty \d+\.\d+\.\d+").unwrap())
+        .stdout(predicate::str::is_match(r"(?m)^schema_version: \S+$").unwrap());
 }
 
 #[test]
@@ -72,6 +189,27 @@ fn help_shows_subcommands() {
         .stdout(predicate::str::contains("version"));
 }
 
+#[test]
+fn run_list_params_shows_descriptions() {
+    snouty()
+        .args(["run", "--list-params"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "--antithesis.duration\n    Desired test duration in minutes",
+        ))
+        .stdout(predicate::str::contains("antithesis.debugging").not());
+}
+
+#[test]
+fn debug_list_params_shows_debugging_params() {
+    snouty()
+        .args(["debug", "--list-params"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--antithesis.debugging.vtime"));
+}
+
 // === Tests for run command ===
 
 #[test]
@@ -119,253 +257,2555 @@ fn run_with_cli_args() {
 }
 
 #[test]
-fn run_with_stdin_json() {
-    let mock_url = start_mock_server(r#"{"launched": true}"#, 200);
+fn run_with_recipient_channels() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
 
     snouty_with_mock(&mock_url)
-        .args(["run", "-w", "basic_test", "--stdin"])
-        .write_stdin(r#"{"antithesis.duration": "60", "antithesis.is_ephemeral": "true"}"#)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--antithesis.report.recipients.email",
+            "team@example.com;ops@example.org",
+            "--antithesis.report.recipients.slack",
+            "https://hooks.slack.com/services/T0/B0/xyz",
+        ])
         .assert()
         .success()
-        .stderr(predicate::str::contains(r#""antithesis.duration": "60""#))
         .stderr(predicate::str::contains(
-            r#""antithesis.is_ephemeral": "true""#,
-        ));
+            r#""antithesis.report.recipients.email": "[REDACTED]""#,
+        ))
+        .stderr(predicate::str::contains(
+            r#""antithesis.report.recipients.slack": "[REDACTED]""#,
+        ))
+        .stderr(predicate::str::contains("hooks.slack.com").not());
 }
 
 #[test]
-fn run_with_custom_properties() {
-    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
-
-    snouty_with_mock(&mock_url)
+fn run_rejects_invalid_recipient_channels() {
+    // No mock server: validation must fail before launching
+    snouty()
         .args([
             "run",
             "-w",
             "basic_test",
-            "--antithesis.duration",
-            "30",
-            "--my.custom.prop",
-            "value",
+            "--antithesis.report.recipients.email",
+            "team",
+            "--antithesis.report.recipients.slack",
+            "team@example.com",
         ])
         .assert()
-        .success()
-        .stderr(predicate::str::contains(r#""my.custom.prop": "value""#));
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"antithesis.report.recipients.email: "team" is not an email address"#,
+        ))
+        .stderr(predicate::str::contains(
+            r#"antithesis.report.recipients.slack: "team@example.com" is not an http(s) URL"#,
+        ));
 }
 
 #[test]
-fn run_stdin_flag_required_for_stdin_input() {
-    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
+fn run_with_stdin_json() {
+    let mock_url = start_mock_server(r#"{"launched": true}"#, 200);
 
-    // Without --stdin flag, stdin is not read even if provided
     snouty_with_mock(&mock_url)
-        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
-        .write_stdin(r#"{"antithesis.duration": "SHOULD_BE_IGNORED"}"#)
+        .args(["run", "-w", "basic_test", "--stdin"])
+        .write_stdin(r#"{"antithesis.duration": "60", "antithesis.is_ephemeral": "true"}"#)
         .assert()
         .success()
-        .stderr(predicate::str::contains(r#""antithesis.duration": "30""#))
-        .stderr(predicate::str::contains("SHOULD_BE_IGNORED").not());
+        .stderr(predicate::str::contains(r#""antithesis.duration": "60""#))
+        .stderr(predicate::str::contains(
+            r#""antithesis.is_ephemeral": "true""#,
+        ));
 }
 
 #[test]
-fn run_with_k8s_webhook() {
-    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+fn run_show_source_labels_preview() {
+    let config = config_file(r#"{ env_params: { SNOUTY_TEST_SOURCE: "antithesis.source" } }"#);
 
-    snouty_with_mock(&mock_url)
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .env("SNOUTY_TEST_SOURCE", "ci")
         .args([
             "run",
-            "--webhook",
-            "basic_k8s_test",
+            "-w",
+            "basic_test",
+            "--stdin",
+            "--show-source",
+            "--print-curl",
+            "--label",
+            "job=7",
             "--antithesis.duration",
-            "30",
+            "60",
         ])
+        .write_stdin(r#"{"antithesis.duration": "30", "antithesis.description": "nightly"}"#)
         .assert()
-        .success();
+        .success()
+        .stderr(
+            predicate::str::is_match(
+                r#""antithesis.source": \{\s*"source": "config",\s*"value": "ci"\s*\}"#,
+            )
+            .unwrap(),
+        )
+        .stderr(
+            predicate::str::is_match(
+                r#""antithesis.description": \{\s*"source": "stdin",\s*"value": "nightly"\s*\}"#,
+            )
+            .unwrap(),
+        )
+        .stderr(
+            predicate::str::is_match(
+                r#""antithesis.duration": \{\s*"source": "cli",\s*"value": "60"\s*\}"#,
+            )
+            .unwrap(),
+        )
+        .stderr(
+            predicate::str::is_match(
+                r#""antithesis.labels.job": \{\s*"source": "cli",\s*"value": "7"\s*\}"#,
+            )
+            .unwrap(),
+        );
 }
 
 #[test]
-fn run_with_custom_webhook() {
-    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
-
-    snouty_with_mock(&mock_url)
+fn no_merge_rejects_stdin_and_args_together() {
+    snouty_with_mock("http://127.0.0.1:1")
         .args([
             "run",
             "-w",
-            "my_custom_webhook",
+            "basic_test",
+            "--stdin",
+            "--no-merge",
             "--antithesis.duration",
             "30",
         ])
+        .write_stdin(r#"{"antithesis.duration": "60"}"#)
         .assert()
-        .success();
-}
-
-// === Tests for debug command ===
-
-#[test]
-fn debug_with_cli_args() {
-    let mock_url = start_mock_server(r#"{"session": "started"}"#, 200);
+        .failure()
+        .stderr(predicate::str::contains("--no-merge"));
 
-    snouty_with_mock(&mock_url)
+    snouty_with_mock("http://127.0.0.1:1")
         .args([
             "debug",
-            "--antithesis.debugging.input_hash",
-            "abc123",
-            "--antithesis.debugging.session_id",
-            "sess-456",
+            "--stdin",
+            "--no-merge",
             "--antithesis.debugging.vtime",
-            "1234567890",
+            "1.5",
         ])
+        .write_stdin(r#"{"antithesis.debugging.vtime": "2.5"}"#)
         .assert()
-        .success()
-        .stderr(predicate::str::contains(
-            r#""antithesis.debugging.input_hash": "abc123""#,
-        ));
-}
-
-#[test]
-fn debug_with_moment_from_format() {
-    let mock_url = start_mock_server(r#"{"debugging": true}"#, 200);
-    let moment_input = r#"Moment.from({ session_id: "f89d5c11f5e3bf5e4bb3641809800cee-44-22", input_hash: "6057726200491963783", vtime: 329.8037810830865 })"#;
+        .failure()
+        .stderr(predicate::str::contains("--no-merge"));
 
-    snouty_with_mock(&mock_url)
-        .args(["debug", "--stdin"])
-        .write_stdin(moment_input)
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--stdin",
+            "--no-merge",
+            "--print-curl",
+        ])
+        .write_stdin(r#"{"antithesis.duration": "60"}"#)
         .assert()
         .success()
-        .stderr(predicate::str::contains(
-            r#""antithesis.debugging.session_id": "f89d5c11f5e3bf5e4bb3641809800cee-44-22""#,
-        ))
-        .stderr(predicate::str::contains(
-            r#""antithesis.debugging.input_hash": "6057726200491963783""#,
-        ))
-        .stderr(predicate::str::contains(
-            r#""antithesis.debugging.vtime": "329.8037810830865""#,
-        ));
+        .stdout(predicate::str::contains(r#""antithesis.duration":"60""#));
 }
 
 #[test]
-fn debug_with_stdin_json() {
-    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
-    let json = r#"{
-        "antithesis.debugging.input_hash": "abc",
-        "antithesis.debugging.session_id": "sess",
-        "antithesis.debugging.vtime": "123"
-    }"#;
+fn run_print_curl_includes_signature() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_SIGNING_KEY", "secret")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--signature-header",
+            "X-Gateway-Signature",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("-H 'x-gateway-signature: [0-9a-f]{64}'").unwrap());
 
-    snouty_with_mock(&mock_url)
-        .args(["debug", "--stdin"])
-        .write_stdin(json)
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
         .assert()
         .success()
-        .stderr(predicate::str::contains(
-            r#""antithesis.debugging.input_hash": "abc""#,
-        ));
+        .stdout(predicate::str::contains("signature").not());
 }
 
-// === Validation error tests ===
+/// Run snouty through `sh` with each file opened on fd 3, 4, and so on.
+#[cfg(unix)]
+fn snouty_with_fds(mock_url: &str, files: &[&std::path::Path]) -> Command {
+    let redirects: String = files
+        .iter()
+        .enumerate()
+        .map(|(i, path)| format!(" {}<'{}'", i + 3, path.display()))
+        .collect();
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c")
+        .arg(format!("exec \"$0\" \"$@\"{}", redirects))
+        .arg(env!("CARGO_BIN_EXE_snouty"));
+    with_mock_env(with_test_env(cmd), mock_url)
+}
 
+#[cfg(unix)]
 #[test]
-fn debug_fails_missing_required_fields() {
-    let mock_url = start_mock_server(r#"{}"#, 200);
+fn run_merges_params_from_fds() {
+    let base = config_file(r#"{"antithesis.duration": "30", "antithesis.description": "base"}"#);
+    let overlay =
+        config_file(r#"{"antithesis.description": "overlay", "antithesis.source": "ci"}"#);
 
-    snouty_with_mock(&mock_url)
-        .args(["debug", "--antithesis.debugging.input_hash", "abc"])
+    snouty_with_fds("http://127.0.0.1:1", &[base.path(), overlay.path()])
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--params-fd",
+            "3",
+            "--params-fd",
+            "4",
+            "--antithesis.source",
+            "cli",
+        ])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("validation failed"));
+        .success()
+        .stdout(predicate::str::contains(r#""antithesis.duration":"30""#))
+        .stdout(predicate::str::contains(
+            r#""antithesis.description":"overlay""#,
+        ))
+        .stdout(predicate::str::contains(r#""antithesis.source":"cli""#));
 }
 
+#[cfg(unix)]
 #[test]
-fn debug_rejects_custom_properties() {
-    let mock_url = start_mock_server(r#"{}"#, 200);
-
-    snouty_with_mock(&mock_url)
+fn run_reports_unreadable_fd() {
+    snouty_with_mock("http://127.0.0.1:1")
         .args([
-            "debug",
-            "--antithesis.debugging.input_hash",
-            "abc",
-            "--antithesis.debugging.session_id",
-            "sess",
-            "--antithesis.debugging.vtime",
-            "123",
-            "--my.custom.prop",
-            "value",
+            "run",
+            "-w",
+            "basic_test",
+            "--params-fd",
+            "97",
+            "--antithesis.duration",
+            "30",
         ])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("validation failed"));
+        .stderr(predicate::str::contains("failed to read fd 97"));
 }
 
-// === Input error tests ===
-
 #[test]
-fn run_fails_on_invalid_json_stdin() {
-    let mock_url = start_mock_server(r#"{}"#, 200);
+fn run_inline_empty_value_is_sent_as_empty_string() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration=30",
+            "--antithesis.description=",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"{"params":{"antithesis.description":"","antithesis.duration":"30"}}"#,
+        ));
 
-    snouty_with_mock(&mock_url)
-        .args(["run", "-w", "basic_test", "--stdin"])
-        .write_stdin("not valid json")
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration=30",
+        ])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("invalid JSON"));
+        .success()
+        .stdout(predicate::str::contains("antithesis.description").not());
 }
 
 #[test]
-fn run_fails_on_missing_value() {
-    let mock_url = start_mock_server(r#"{}"#, 200);
-
-    snouty_with_mock(&mock_url)
-        .args(["run", "-w", "basic_test", "--antithesis.duration"])
-        .assert()
+fn assert_no_leak_blocks_secrets_under_unexpected_keys() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--assert-no-leak",
+            "--antithesis.duration",
+            "30",
+            "--antithesis.description",
+            "password is testpass",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the preview would show a secret under antithesis.description",
+        ))
+        .stderr(predicate::str::contains("Requesting Antithesis test run").not());
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--assert-no-leak",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+            "--antithesis.report.recipients",
+            "team@example.com",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn connect_timeout_is_configurable() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--connect-timeout",
+            "3s",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_CONNECT_TIMEOUT", "soon")
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--connect-timeout"));
+}
+
+#[test]
+fn oversized_body_fails_before_sending() {
+    // Nothing is listening here, so sending would fail differently
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_MAX_BODY_BYTES", "64")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--antithesis.duration",
+            "30",
+            "--antithesis.description",
+            &"x".repeat(100),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "over the ANTITHESIS_MAX_BODY_BYTES limit of 64",
+        ))
+        .stderr(predicate::str::contains("Requesting Antithesis test run").not());
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_MAX_BODY_BYTES", "4096")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn double_dash_separates_flags_from_params() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--",
+            "--antithesis.duration",
+            "30",
+            "--no-preview",
+            "yes",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"{"params":{"antithesis.duration":"30","no-preview":"yes"}}"#,
+        ));
+
+    // Params before the -- would otherwise swallow the flags after them
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--antithesis.duration",
+            "30",
+            "--",
+            "--print-curl",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"unexpected argument "--antithesis.duration" before --"#,
+        ));
+}
+
+#[test]
+fn run_images_from_compose_file() {
+    let compose =
+        config_file("services:\n  app:\n    image: app:1.2\n  db:\n    image: postgres:16\n");
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .arg("run")
+        .args(["-w", "basic_test", "--print-curl", "--images-from"])
+        .arg(compose.path())
+        .args(["--antithesis.duration", "30"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.images":"app:1.2;postgres:16""#,
+        ));
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .arg("run")
+        .args(["-w", "basic_test", "--print-curl", "--images-from"])
+        .arg(compose.path())
+        .args([
+            "--antithesis.images",
+            "override:1",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.images":"override:1""#,
+        ));
+}
+
+#[test]
+fn no_envelope_sends_params_at_top_level() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"-d '{"params":{"antithesis.duration":"30"}}'"#,
+        ));
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--no-envelope",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"-d '{"antithesis.duration":"30"}'"#,
+        ));
+}
+
+#[test]
+fn run_with_custom_properties() {
+    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--antithesis.duration",
+            "30",
+            "--my.custom.prop",
+            "value",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(r#""my.custom.prop": "value""#));
+}
+
+#[test]
+fn run_stdin_flag_required_for_stdin_input() {
+    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
+
+    // Without --stdin flag, stdin is not read even if provided
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .write_stdin(r#"{"antithesis.duration": "SHOULD_BE_IGNORED"}"#)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(r#""antithesis.duration": "30""#))
+        .stderr(predicate::str::contains("SHOULD_BE_IGNORED").not());
+}
+
+#[test]
+fn run_with_k8s_webhook() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "--webhook",
+            "basic_k8s_test",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_with_custom_webhook() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "my_custom_webhook",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_waits_before_launching() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--after",
+            "10ms",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Waiting 10ms before launching"))
+        .stderr(predicate::str::contains("Expect a report email"));
+}
+
+#[test]
+fn run_labels_are_sent_with_prefix() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--label",
+            "ci_job=1234",
+            "--label",
+            "branch=main",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.labels.ci_job":"1234""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""antithesis.labels.branch":"main""#,
+        ));
+}
+
+#[test]
+fn run_env_is_sent_with_prefix() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--env",
+            "ANTITHESIS_SDK_LOCAL_OUTPUT=/tmp/out",
+            "--env",
+            "RUST_LOG=debug",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.env.ANTITHESIS_SDK_LOCAL_OUTPUT":"/tmp/out""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""antithesis.env.RUST_LOG":"debug""#,
+        ));
+}
+
+#[test]
+fn run_rejects_invalid_env_name() {
+    snouty()
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--env",
+            "MY-VAR=1",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "must be a valid environment variable name",
+        ));
+}
+
+#[test]
+fn run_rejects_invalid_label_key() {
+    snouty()
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--label",
+            "ci-job=1234",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must be a simple identifier"));
+}
+
+#[test]
+fn run_writes_response_to_file() {
+    let mock_url = start_mock_server(r#"{"run_id": "abc"}"#, 200);
+    let out = tempfile::NamedTempFile::new().unwrap();
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--response-out"])
+        .arg(out.path())
+        .args(["--antithesis.duration", "30"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    assert_eq!(
+        std::fs::read_to_string(out.path()).unwrap(),
+        r#"{"run_id": "abc"}"#
+    );
+}
+
+#[test]
+fn run_writes_response_to_stdout() {
+    let mock_url = start_mock_server(r#"{"run_id": "abc"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--response-out",
+            "-",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(r#"{"run_id": "abc"}"#.to_string() + "\n");
+}
+
+#[test]
+fn run_prints_response_field() {
+    let mock_url = start_mock_server(r#"{"run_id": "abc", "status": "queued"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-field",
+            "/run_id",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout("abc\n");
+}
+
+#[test]
+fn run_fails_when_printed_field_is_missing() {
+    let mock_url = start_mock_server(r#"{"status": "queued"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-field",
+            "/run_id",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the response has no /run_id field",
+        ));
+}
+
+#[test]
+fn run_rejects_unsupported_content_encoding() {
+    let mock_url = start_raw_mock_server(
+        "HTTP/1.1 200 OK\r\nContent-Encoding: br\r\nContent-Length: 2\r\nConnection: close\r\n\r\n{}",
+    );
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"unsupported Content-Encoding "br""#,
+        ));
+}
+
+#[test]
+fn run_reports_api_error_before_content_encoding() {
+    let mock_url = start_raw_mock_server(
+        "HTTP/1.1 400 Bad Request\r\nContent-Encoding: br\r\nContent-Length: 11\r\nConnection: close\r\n\r\nbad webhook",
+    );
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "API error: 400 on POST /launch/basic_test - bad webhook",
+        ))
+        .stderr(predicate::str::contains("Content-Encoding").not());
+}
+
+#[test]
+fn run_renders_template() {
+    let mock_url = start_mock_server(r#"{"run": {"id": "abc"}}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--template",
+            "{webhook}: launched, eta={eta}, id={response.run.id}",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r"^basic_test: launched, eta=[A-Z][a-z]{2} \d+ at \d+:\d{2} [AP]M, id=abc\n$",
+            )
+            .unwrap(),
+        );
+}
+
+#[test]
+fn run_rejects_unknown_template_placeholder() {
+    // No mock server: the template is checked before launching
+    snouty()
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--template",
+            "{webhook} {run_id}",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unknown --template placeholder {run_id}",
+        ));
+}
+
+#[test]
+fn run_params_only_exports_redacted_params() {
+    let out = tempfile::NamedTempFile::new().unwrap();
+
+    // No mock server: exporting must not launch
+    snouty()
+        .args(["run", "-w", "basic_test", "--stdin", "--params-only"])
+        .arg(out.path())
+        .args(["--antithesis.report.recipients", "team@example.com"])
+        .write_stdin(r#"{"antithesis.duration": "30"}"#)
+        .assert()
+        .success();
+
+    let exported: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(out.path()).unwrap()).unwrap();
+    assert_eq!(
+        exported,
+        serde_json::json!({
+            "antithesis.duration": "30",
+            "antithesis.report.recipients": "[REDACTED]"
+        })
+    );
+}
+
+#[test]
+fn run_params_only_can_include_secrets() {
+    let out = tempfile::NamedTempFile::new().unwrap();
+
+    snouty()
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--include-secrets",
+            "--params-only",
+        ])
+        .arg(out.path())
+        .args(["--antithesis.report.recipients", "team@example.com"])
+        .assert()
+        .success();
+
+    let exported: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(out.path()).unwrap()).unwrap();
+    assert_eq!(
+        exported,
+        serde_json::json!({ "antithesis.report.recipients": "team@example.com" })
+    );
+}
+
+#[test]
+fn run_posts_body_file_verbatim() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+    let mut body_file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        body_file,
+        r#"{{"params": {{"antithesis.duration": "30", "antithesis.integrations.github.token": "s3cret"}}, "extra": 1}}"#
+    )
+    .unwrap();
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--body-file"])
+        .arg(body_file.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(r#""antithesis.duration": "30""#))
+        .stderr(predicate::str::contains(
+            r#""antithesis.integrations.github.token": "[REDACTED]""#,
+        ))
+        .stderr(predicate::str::contains("s3cret").not());
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--print-curl", "--body-file"])
+        .arg(body_file.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"-d '{"params": {"antithesis.duration": "30", "antithesis.integrations.github.token": "s3cret"}, "extra": 1}'"#,
+        ));
+}
+
+#[test]
+fn run_rejects_non_object_body_file() {
+    let mut body_file = tempfile::NamedTempFile::new().unwrap();
+    write!(body_file, "[1, 2, 3]").unwrap();
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["run", "-w", "basic_test", "--body-file"])
+        .arg(body_file.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected a JSON object"));
+}
+
+#[test]
+fn run_humanize_duration_converts_to_minutes() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--humanize-duration",
+            "--antithesis.duration",
+            "1h",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""antithesis.duration":"60""#));
+}
+
+#[test]
+fn run_injects_params_from_configured_env_vars() {
+    let config = config_file(
+        r#"{ env_params: { CI_COMMIT_SHA: "antithesis.labels.commit", GITHUB_RUN_ID: "antithesis.labels.run" } }"#,
+    );
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .env("CI_COMMIT_SHA", "abc123")
+        .env_remove("GITHUB_RUN_ID")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.labels.commit":"abc123""#,
+        ))
+        .stdout(predicate::str::contains("antithesis.labels.run").not());
+}
+
+#[test]
+fn snouty_env_selects_config_profile() {
+    let config = config_file(
+        r#"{ profiles: { staging: { tenant: "acme-staging" }, prod: { tenant: "acme" } } }"#,
+    );
+    let run = |cmd: &mut Command| {
+        cmd.env("SNOUTY_CONFIG", config.path())
+            .env("ANTITHESIS_USERNAME", "user")
+            .env("ANTITHESIS_PASSWORD", "pass")
+            .env_remove("ANTITHESIS_TENANT")
+            .env_remove("ANTITHESIS_BASE_URL")
+            .args([
+                "run",
+                "-w",
+                "basic_test",
+                "--print-curl",
+                "--antithesis.duration",
+                "30",
+            ])
+            .assert()
+    };
+
+    run(snouty().env("SNOUTY_ENV", "staging"))
+        .success()
+        .stdout(predicate::str::contains(
+            "'https://acme-staging.antithesis.com/api/v1/launch/basic_test'",
+        ));
+
+    // --profile wins over SNOUTY_ENV
+    run(snouty().env("SNOUTY_ENV", "staging").arg("--profile=prod"))
+        .success()
+        .stdout(predicate::str::contains(
+            "'https://acme.antithesis.com/api/v1/launch/basic_test'",
+        ));
+
+    run(snouty().env("SNOUTY_ENV", "dev"))
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"unknown profile "dev" (defined profiles: prod, staging)"#,
+        ));
+}
+
+#[test]
+fn print_config_shows_resolved_settings_and_sources() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_RETRY_BASE_DELAY", "2s")
+        .args(["run", "--print-config", "--retries", "3"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "base_url:         http://127.0.0.1:1 (env ANTITHESIS_BASE_URL)",
+        ))
+        .stdout(predicate::str::contains(
+            "username:         te*** (env ANTITHESIS_USERNAME)",
+        ))
+        .stdout(predicate::str::contains("testpass").not())
+        .stdout(predicate::str::contains(
+            "tenant:           testtenant (env ANTITHESIS_TENANT)",
+        ))
+        .stdout(predicate::str::contains("connect_timeout:  10s (default)"))
+        .stdout(predicate::str::contains("retries:          3 (--retries)"))
+        .stdout(predicate::str::contains(
+            "retry_base_delay: 2s (env ANTITHESIS_RETRY_BASE_DELAY)",
+        ))
+        .stdout(predicate::str::contains("timeout:          30s (fixed)"));
+
+    // Flags are reported as flags even when they match the default or the
+    // environment variable
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_CONNECT_TIMEOUT", "5s")
+        .args([
+            "run",
+            "--print-config",
+            "--retries",
+            "0",
+            "--connect-timeout",
+            "5s",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "connect_timeout:  5s (--connect-timeout)",
+        ))
+        .stdout(predicate::str::contains("retries:          0 (--retries)"));
+}
+
+#[test]
+fn print_config_names_the_profile() {
+    let config = config_file(r#"{ profiles: { staging: { tenant: "acme-staging" } } }"#);
+
+    snouty()
+        .env("SNOUTY_CONFIG", config.path())
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env_remove("ANTITHESIS_TENANT")
+        .env_remove("ANTITHESIS_BASE_URL")
+        .args(["--profile", "staging", "run", "--print-config"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "tenant:           acme-staging (profile staging)",
+        ))
+        .stdout(predicate::str::contains(
+            "base_url:         https://acme-staging.antithesis.com/api/v1 (default)",
+        ))
+        .stdout(predicate::str::contains(
+            "username:         (not set) (none)",
+        ));
+}
+
+#[test]
+fn launch_path_template_from_config_and_env() {
+    let config = config_file(r#"{ launch_path: "/tenants/acme/launch/{webhook}" }"#);
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "'http://127.0.0.1:1/tenants/acme/launch/basic_test'",
+        ));
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .env("SNOUTY_LAUNCH_PATH", "/v2/{webhook}")
+        .args([
+            "debug",
+            "--print-curl",
+            "--antithesis.debugging.input_hash",
+            "abc",
+            "--antithesis.debugging.session_id",
+            "sess",
+            "--antithesis.debugging.vtime",
+            "1.5",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "'http://127.0.0.1:1/v2/debugging'",
+        ));
+}
+
+#[test]
+fn debug_validates_vtime_range() {
+    let debug = |start: &str, end: &str| {
+        let mut cmd = snouty_with_mock("http://127.0.0.1:1");
+        cmd.args([
+            "debug",
+            "--print-curl",
+            "--antithesis.debugging.input_hash",
+            "abc",
+            "--antithesis.debugging.session_id",
+            "sess",
+            "--antithesis.debugging.vtime",
+            "1.5",
+            "--antithesis.debugging.vtime_start",
+            start,
+            "--antithesis.debugging.vtime_end",
+            end,
+        ]);
+        cmd
+    };
+
+    debug("1.5", "10")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.debugging.vtime_end":"10""#,
+        ));
+    debug("10", "1.5")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "antithesis.debugging.vtime_start 10 is after antithesis.debugging.vtime_end 1.5",
+        ));
+}
+
+#[test]
+fn run_query_params_are_encoded_in_url() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--query",
+            "priority=high",
+            "--query",
+            "note=a&b",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "'http://127.0.0.1:1/launch/basic_test?priority=high&note=a%26b'",
+        ));
+}
+
+#[test]
+fn json_log_format_writes_json_lines() {
+    let output = snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--log-format",
+            "json",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let record: serde_json::Value = stderr
+        .lines()
+        .filter(|line| line.starts_with('{'))
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .find(|record| record["level"] == "DEBUG")
+        .expect("a debug log line");
+    assert!(record["target"].is_string());
+    assert!(record["message"].is_string());
+    assert!(record["timestamp"].is_string());
+}
+
+#[test]
+fn run_fails_on_invalid_config_file() {
+    let config = config_file("{ not valid");
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid config file"));
+}
+
+#[test]
+fn run_no_preview_omits_params_but_keeps_eta() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--no-preview",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Requesting Antithesis test run").not())
+        .stderr(predicate::str::contains("Expect a report email"));
+}
+
+#[test]
+fn run_print_curl_does_not_send() {
+    // Nothing is listening here, so sending would fail
+    let unused_url = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+
+    snouty_with_mock(&unused_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "curl -X POST '{}/launch/basic_test'",
+            unused_url
+        )))
+        .stdout(predicate::str::contains(
+            r#"-u "$ANTITHESIS_USERNAME:$ANTITHESIS_PASSWORD""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#"-d '{"params":{"antithesis.duration":"30"}}'"#,
+        ))
+        .stdout(predicate::str::contains("testpass").not());
+}
+
+// === Tests for debug command ===
+
+#[test]
+fn debug_with_cli_args() {
+    let mock_url = start_mock_server(r#"{"session": "started"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "debug",
+            "--antithesis.debugging.input_hash",
+            "abc123",
+            "--antithesis.debugging.session_id",
+            "sess-456",
+            "--antithesis.debugging.vtime",
+            "1234567890",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.input_hash": "abc123""#,
+        ));
+}
+
+#[test]
+fn debug_takes_moment_from_report_url() {
+    let mock_url = start_mock_server(
+        r#"{"moment": {"session_id": "sess-456", "input_hash": "abc123", "vtime": 329.8}}"#,
+        200,
+    );
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "debug",
+            "--report-url",
+            "/reports/triage-1",
+            "--print-curl",
+            "--antithesis.report.recipients",
+            "team@example.com",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/launch/debugging'"))
+        .stdout(predicate::str::contains(
+            r#""antithesis.debugging.session_id":"sess-456""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""antithesis.debugging.input_hash":"abc123""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""antithesis.debugging.vtime":"329.8""#,
+        ))
+        .stdout(predicate::str::contains(
+            r#""antithesis.report.recipients":"team@example.com""#,
+        ));
+}
+
+#[test]
+fn debug_with_moment_from_format() {
+    let mock_url = start_mock_server(r#"{"debugging": true}"#, 200);
+    let moment_input = r#"Moment.from({ session_id: "f89d5c11f5e3bf5e4bb3641809800cee-44-22", input_hash: "6057726200491963783", vtime: 329.8037810830865 })"#;
+
+    snouty_with_mock(&mock_url)
+        .args(["debug", "--stdin"])
+        .write_stdin(moment_input)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.session_id": "f89d5c11f5e3bf5e4bb3641809800cee-44-22""#,
+        ))
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.input_hash": "6057726200491963783""#,
+        ))
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.vtime": "329.8037810830865""#,
+        ));
+}
+
+#[test]
+fn debug_print_moment_echoes_moment() {
+    let mock_url = start_mock_server(r#"{"debugging": true}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "debug",
+            "--print-moment",
+            "--antithesis.debugging.input_hash",
+            "abc123",
+            "--antithesis.debugging.session_id",
+            "sess-456",
+            "--antithesis.debugging.vtime",
+            "329.5",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#"Moment.from({ session_id: "sess-456", input_hash: "abc123", vtime: 329.5 })"#,
+        ));
+}
+
+#[test]
+fn debug_partial_moment_asks_for_input_hash() {
+    let moment_input = r#"Moment.from({ session_id: "sess-456", vtime: 329.8 })"#;
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["debug", "--stdin"])
+        .write_stdin(moment_input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Moment.from is missing antithesis.debugging.input_hash; supply it on the command line with --antithesis.debugging.input_hash <value>",
+        ));
+}
+
+#[test]
+fn debug_partial_moment_merges_input_hash_from_cli() {
+    let mock_url = start_mock_server(r#"{"debugging": true}"#, 200);
+    let moment_input = r#"Moment.from({ session_id: "sess-456", vtime: 329.8 })"#;
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "debug",
+            "--stdin",
+            "--antithesis.debugging.input_hash",
+            "abc123",
+        ])
+        .write_stdin(moment_input)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.input_hash": "abc123""#,
+        ))
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.session_id": "sess-456""#,
+        ));
+}
+
+#[test]
+fn debug_writes_response_to_file() {
+    let mock_url = start_mock_server(r#"{"session": "started"}"#, 200);
+    let out = tempfile::NamedTempFile::new().unwrap();
+
+    snouty_with_mock(&mock_url)
+        .args(["debug", "--response-out"])
+        .arg(out.path())
+        .args([
+            "--antithesis.debugging.input_hash",
+            "abc123",
+            "--antithesis.debugging.session_id",
+            "sess-456",
+            "--antithesis.debugging.vtime",
+            "1234567890",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    assert_eq!(
+        std::fs::read_to_string(out.path()).unwrap(),
+        r#"{"session": "started"}"#
+    );
+}
+
+#[test]
+fn debug_params_format_forces_parser() {
+    let moment_input = r#"Moment.from({ session_id: "sess", input_hash: "abc", vtime: 1.5 })"#;
+
+    // Detected as a Moment by default, but forcing JSON skips detection
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "debug",
+            "--stdin",
+            "--params-format",
+            "json",
+            "--print-curl",
+        ])
+        .write_stdin(moment_input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid JSON"));
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "debug",
+            "--stdin",
+            "--params-format",
+            "moment",
+            "--print-curl",
+        ])
+        .write_stdin(r#"{"antithesis.debugging.vtime": "1.5"}"#)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("expected Moment.from"));
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "debug",
+            "--stdin",
+            "--params-format",
+            "moment",
+            "--print-curl",
+        ])
+        .write_stdin(moment_input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#""antithesis.debugging.session_id":"sess""#,
+        ));
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--stdin",
+            "--params-format",
+            "moment",
+        ])
+        .write_stdin(moment_input)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only supported by snouty debug"));
+}
+
+#[test]
+fn debug_with_stdin_json() {
+    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
+    let json = r#"{
+        "antithesis.debugging.input_hash": "abc",
+        "antithesis.debugging.session_id": "sess",
+        "antithesis.debugging.vtime": "123"
+    }"#;
+
+    snouty_with_mock(&mock_url)
+        .args(["debug", "--stdin"])
+        .write_stdin(json)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.input_hash": "abc""#,
+        ));
+}
+
+#[test]
+fn debug_parses_json_mentioning_moment_as_json() {
+    let mock_url = start_mock_server(r#"{"ok": true}"#, 200);
+    let json = r#"{
+        "antithesis.debugging.input_hash": "abc",
+        "antithesis.debugging.session_id": "sess",
+        "antithesis.debugging.vtime": "123",
+        "antithesis.report.recipients": "see Moment.from({ vtime: 1 })"
+    }"#;
+
+    snouty_with_mock(&mock_url)
+        .args(["debug", "--stdin"])
+        .write_stdin(json)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("parsing input as JSON"))
+        .stderr(predicate::str::contains(
+            r#""antithesis.debugging.session_id": "sess""#,
+        ));
+}
+
+// === Validation error tests ===
+
+#[test]
+fn debug_fails_missing_required_fields() {
+    let mock_url = start_mock_server(r#"{}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args(["debug", "--antithesis.debugging.input_hash", "abc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("validation failed"));
+}
+
+#[test]
+fn debug_summary_only_writes_errors_to_file() {
+    let error_file = tempfile::NamedTempFile::new().unwrap();
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["debug", "--summary-only", "--error-file"])
+        .arg(error_file.path())
+        .args(["--antithesis.debugging.input_hash", "abc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "error: validation failed: 3 errors\n",
+        ))
+        .stderr(predicate::str::contains("validation failed:\n").not());
+
+    let errors = std::fs::read_to_string(error_file.path()).unwrap();
+    let lines: Vec<&str> = errors.lines().collect();
+    assert_eq!(lines.len(), 3, "{}", errors);
+    assert!(lines[0].contains(r#""antithesis.debugging.session_id" is a required property"#));
+    assert!(lines[1].contains(r#""antithesis.debugging.vtime" is a required property"#));
+    assert!(lines[2].contains("'antithesis.debugging.input_hash' was unexpected"));
+}
+
+#[test]
+fn debug_rejects_custom_properties() {
+    let mock_url = start_mock_server(r#"{}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "debug",
+            "--antithesis.debugging.input_hash",
+            "abc",
+            "--antithesis.debugging.session_id",
+            "sess",
+            "--antithesis.debugging.vtime",
+            "123",
+            "--my.custom.prop",
+            "value",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("validation failed"));
+}
+
+// === Input error tests ===
+
+#[test]
+fn run_fails_on_invalid_json_stdin() {
+    let mock_url = start_mock_server(r#"{}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--stdin"])
+        .write_stdin("not valid json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid JSON"));
+}
+
+#[test]
+fn run_fails_on_missing_value() {
+    let mock_url = start_mock_server(r#"{}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing value"));
+}
+
+#[test]
+fn run_fails_on_unexpected_arg() {
+    let mock_url = start_mock_server(r#"{}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "notaflag"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument"));
+}
+
+#[test]
+fn run_accepts_hyphen_value_by_default() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--my.offset",
+            "-30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""my.offset":"-30""#));
+}
+
+#[test]
+fn run_strict_args_rejects_hyphen_value() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--strict-args",
+            "--antithesis.duration",
+            "-30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"suspicious value "-30" for --antithesis.duration"#,
+        ));
+}
+
+#[test]
+fn run_fails_without_webhook() {
+    let mock_url = start_mock_server(r#"{}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing --webhook"));
+}
+
+#[test]
+fn run_takes_webhook_from_stdin_params() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["run", "--stdin", "--print-curl"])
+        .write_stdin(r#"{"webhook": "basic_k8s_test", "antithesis.duration": "30"}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/launch/basic_k8s_test'"))
+        .stdout(predicate::str::contains(
+            r#"-d '{"params":{"antithesis.duration":"30"}}'"#,
+        ));
+
+    // The CLI flag wins, and the key is still stripped
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["run", "-w", "basic_test", "--stdin", "--print-curl"])
+        .write_stdin(r#"{"antithesis.webhook": "basic_k8s_test", "antithesis.duration": "30"}"#)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/launch/basic_test'"))
+        .stdout(predicate::str::contains("basic_k8s_test").not())
+        .stderr(predicate::str::contains(
+            "warning: --webhook basic_test overrides the webhook basic_k8s_test in the params",
+        ));
+}
+
+#[test]
+fn strict_warnings_makes_override_warning_fatal() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "--strict-warnings",
+            "run",
+            "-w",
+            "basic_test",
+            "--stdin",
+            "--print-curl",
+        ])
+        .write_stdin(r#"{"webhook": "basic_k8s_test", "antithesis.duration": "30"}"#)
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains(
+            "error: --webhook basic_test overrides the webhook basic_k8s_test in the params (warnings are errors with --strict-warnings)",
+        ));
+
+    // Without a warning, the flag changes nothing
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "--strict-warnings",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_uses_default_webhook_from_config() {
+    let config = config_file(r#"{ default_webhook: "basic_k8s_test" }"#);
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .args(["run", "--print-curl", "--antithesis.duration", "30"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/launch/basic_k8s_test'"))
+        .stderr(predicate::str::contains(
+            "using default_webhook from config: basic_k8s_test",
+        ));
+
+    // An explicit --webhook still wins
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/launch/basic_test'"));
+}
+
+#[test]
+fn run_enforces_supplemental_schema() {
+    let mut supplement = tempfile::NamedTempFile::new().unwrap();
+    supplement
+        .write_all(
+            br#"{"$defs": {"testParams": {"properties": {"mycorp.team": {"type": "string"}}, "required": ["mycorp.team"]}}}"#,
+        )
+        .unwrap();
+    let config = config_file(&format!(
+        "{{ supplemental_schema: {:?} }}",
+        supplement.path().to_str().unwrap()
+    ));
+
+    snouty()
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--only-validate",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("mycorp.team"));
+
+    snouty()
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--only-validate",
+            "--antithesis.duration",
+            "30",
+            "--mycorp.team",
+            "storage",
+        ])
+        .assert()
+        .success();
+}
+
+// === diff tests ===
+
+#[test]
+fn diff_shows_added_removed_and_changed_keys() {
+    let old = config_file(
+        r#"{"antithesis.duration": "30", "antithesis.description": "nightly", "antithesis.report.recipients": "a@example.com"}"#,
+    );
+    let new = config_file(
+        r#"{"antithesis.duration": "60", "antithesis.source": "main", "antithesis.report.recipients": "b@example.com"}"#,
+    );
+
+    snouty()
+        .arg("diff")
+        .arg(old.path())
+        .arg(new.path())
+        .assert()
+        .success()
+        .stdout(concat!(
+            "- antithesis.description: \"nightly\"\n",
+            "~ antithesis.duration: \"30\" -> \"60\"\n",
+            "~ antithesis.report.recipients: \"[REDACTED]\" -> \"[REDACTED]\"\n",
+            "+ antithesis.source: \"main\"\n",
+        ))
+        .stdout(predicate::str::contains("example.com").not());
+}
+
+#[test]
+fn diff_of_identical_files_is_empty() {
+    let params = config_file(r#"{"antithesis.duration": "30"}"#);
+
+    snouty()
+        .arg("diff")
+        .arg(params.path())
+        .arg(params.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("No differences"));
+}
+
+#[test]
+fn diff_reads_toml_files() {
+    let json = config_file(r#"{"antithesis.duration": "30", "antithesis.description": "nightly"}"#);
+    let mut toml = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    toml.write_all(b"[antithesis]\nduration = \"60\"\ndescription = \"nightly\"\n")
+        .unwrap();
+
+    snouty()
+        .arg("diff")
+        .arg(json.path())
+        .arg(toml.path())
+        .assert()
+        .success()
+        .stdout("~ antithesis.duration: \"30\" -> \"60\"\n");
+}
+
+fn params_file(contents: &str) -> tempfile::NamedTempFile {
+    let mut file = tempfile::Builder::new()
+        .suffix(".params")
+        .tempfile()
+        .unwrap();
+    file.write_all(contents.as_bytes()).unwrap();
+    file
+}
+
+#[test]
+fn diff_detects_json_params_file() {
+    let old = params_file(r#"{"antithesis.duration": "30"}"#);
+    let new = params_file(r#"{"antithesis.duration": "60"}"#);
+
+    snouty()
+        .arg("diff")
+        .arg(old.path())
+        .arg(new.path())
+        .assert()
+        .success()
+        .stdout("~ antithesis.duration: \"30\" -> \"60\"\n")
+        .stderr(predicate::str::contains(format!(
+            "parsed {} as JSON",
+            old.path().display()
+        )));
+}
+
+#[test]
+fn diff_can_force_yaml_params_file() {
+    let old = params_file("antithesis.duration: \"30\"\n");
+    let new = params_file("antithesis:\n  duration: \"60\"\n");
+
+    snouty()
+        .args(["diff", "--param-file-format", "yaml"])
+        .arg(old.path())
+        .arg(new.path())
+        .assert()
+        .success()
+        .stdout("~ antithesis.duration: \"30\" -> \"60\"\n")
+        .stderr(predicate::str::contains(format!(
+            "parsing {} as YAML",
+            old.path().display()
+        )));
+
+    snouty()
+        .args(["diff", "--param-file-format", "json"])
+        .arg(old.path())
+        .arg(new.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid JSON"));
+}
+
+// === API error tests ===
+
+#[test]
+fn run_reports_api_errors() {
+    let mock_url = start_mock_server(r#"{"error": "bad request"}"#, 400);
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "API error: 400 on POST /launch/basic_test",
+        ));
+}
+
+#[test]
+fn run_reads_chunked_response() {
+    let mock_url = start_chunked_mock_server(200, &[r#"{"run_id": "#, r#""abc"}"#]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--response-out",
+            "-",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(r#"{"run_id": "abc"}"#.to_string() + "\n");
+}
+
+#[test]
+fn run_reports_full_chunked_error_body() {
+    let mock_url =
+        start_chunked_mock_server(500, &[r#"{"error": "#, r#""gateway "#, r#"timeout"}"#]);
+
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"API error: 500 on POST /launch/basic_test - {"error": "gateway timeout"}"#,
+        ));
+}
+
+#[test]
+fn run_fails_when_expected_field_mismatches() {
+    let mock_url = start_mock_server(r#"{"status": "error"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--expect-field",
+            "status=ok",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "expected status=ok but got status=error",
+        ));
+}
+
+#[test]
+fn run_succeeds_when_expected_field_matches() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--expect-field",
+            "status=ok",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_rejects_malformed_ca_bundle() {
+    let bundle =
+        config_file("-----BEGIN CERTIFICATE-----\nbm90IGEgY2VydA==\n-----END CERTIFICATE-----\n");
+
+    // No mock server: the client must fail to build before anything is sent
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("ANTITHESIS_CA_BUNDLE", bundle.path())
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "invalid CA bundle {}",
+            bundle.path().display()
+        )));
+}
+
+#[test]
+fn run_fail_on_empty_response() {
+    let mock_url = start_mock_server("  \n", 200);
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--fail-on-empty-response",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "unexpected response: 200 OK with an empty body",
+        ));
+
+    let mock_url = start_mock_server("", 200);
+    snouty_with_mock(&mock_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_reports_attempts_after_retry() {
+    let mock_url = start_mock_server_sequence(vec![
+        (503, r#"{"error": "unavailable"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--retries",
+            "2",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("attempts: 2"));
+}
+
+#[test]
+fn run_validates_against_fetched_schema() {
+    let schema = r#"{"$defs": {"testParams": {"type": "object", "required": ["my.required"]}, "debuggingParams": {"type": "object"}}}"#;
+    let mock_url = start_mock_server(schema, 200);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    snouty_with_mock(&mock_url)
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--schema",
+            "/schema.json",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("validation failed"))
+        .stderr(predicate::str::contains("my.required"));
+}
+
+#[test]
+fn run_falls_back_to_bundled_schema() {
+    let mock_url = start_mock_server_sequence(vec![
+        (500, r#"{"error": "unavailable"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    snouty_with_mock(&mock_url)
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--schema",
+            "/schema.json",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: failed to fetch schema from /schema.json",
+        ));
+}
+
+#[test]
+fn run_rejects_broken_fetched_schema() {
+    let schema = r#"{"$defs": {"testParams": {"type": "not-a-type"}, "debuggingParams": {"type": "object"}}}"#;
+    let mock_url = start_mock_server_sequence(vec![(200, schema), (200, r#"{"status": "ok"}"#)]);
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    snouty_with_mock(&mock_url)
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--schema",
+            "/schema.json",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
         .failure()
-        .stderr(predicate::str::contains("missing value"));
+        .stderr(predicate::str::contains(
+            "invalid schema: /schema.json: $defs/testParams",
+        ))
+        .stderr(predicate::str::contains("panicked").not());
 }
 
 #[test]
-fn run_fails_on_unexpected_arg() {
-    let mock_url = start_mock_server(r#"{}"#, 200);
+fn run_rejects_fetched_schema_that_is_not_json() {
+    let mock_url = start_mock_server_sequence(vec![(200, "<html>"), (200, r#"{"status": "ok"}"#)]);
+    let cache_dir = tempfile::tempdir().unwrap();
 
     snouty_with_mock(&mock_url)
-        .args(["run", "-w", "basic_test", "notaflag"])
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--schema",
+            "/schema.json",
+            "--antithesis.duration",
+            "30",
+        ])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("unexpected argument"));
+        .stderr(predicate::str::contains(
+            "invalid schema: /schema.json is not valid JSON",
+        ));
 }
 
 #[test]
-fn run_fails_without_webhook() {
-    let mock_url = start_mock_server(r#"{}"#, 200);
+fn run_uses_bundled_schema_when_fetch_fails() {
+    let mock_url =
+        start_mock_server_sequence(vec![(404, "not found"), (200, r#"{"status": "ok"}"#)]);
+    let cache_dir = tempfile::tempdir().unwrap();
 
     snouty_with_mock(&mock_url)
-        .args(["run", "--antithesis.duration", "30"])
+        .env("XDG_CACHE_HOME", cache_dir.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--schema",
+            "/schema.json",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("using the bundled schema"));
+}
+
+#[test]
+fn run_trace_request_redacts_auth() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--trace-request",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(format!(
+            "> POST {}/launch/basic_test",
+            mock_url
+        )))
+        .stderr(predicate::str::contains("> Authorization: [REDACTED]"))
+        .stderr(predicate::str::contains(
+            r#"> {"params":{"antithesis.duration":"30"}}"#,
+        ))
+        .stderr(predicate::str::contains("< 200 OK"))
+        .stderr(predicate::str::contains(r#"< {"status": "ok"}"#))
+        .stderr(predicate::str::contains("dGVzdHVzZXI6dGVzdHBhc3M").not());
+}
+
+#[test]
+fn run_repeat_launches_multiple_times() {
+    let mock_url = start_mock_server_sequence(vec![
+        (200, r#"{"status": "ok"}"#),
+        (500, r#"{"error": "boom"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--repeat",
+            "3",
+            "--verbose",
+            "--antithesis.duration",
+            "30",
+        ])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("--webhook"));
+        .stderr(predicate::str::contains("Run 1/3: launched"))
+        .stderr(predicate::str::contains("Run 2/3: failed: API error: 500"))
+        .stderr(predicate::str::contains("Run 3/3: launched"))
+        .stderr(predicate::str::contains("Launched 2 of 3 runs"))
+        .stderr(predicate::str::contains("1 of 3 launches failed"))
+        .stdout("launched=2 failed=1\n");
 }
 
-// === API error tests ===
+#[test]
+fn run_repeat_lists_runs_only_with_verbose() {
+    let mock_url = start_mock_server_sequence(vec![
+        (200, r#"{"status": "ok"}"#),
+        (500, r#"{"error": "boom"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--repeat",
+            "3",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Run 1/3").not())
+        .stderr(predicate::str::contains("Launched 2 of 3 runs"))
+        .stdout("launched=2 failed=1\n");
+}
 
 #[test]
-fn run_reports_api_errors() {
-    let mock_url = start_mock_server(r#"{"error": "bad request"}"#, 400);
+fn run_repeat_verbose_conflicts_with_count_only() {
+    snouty()
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--repeat",
+            "3",
+            "--verbose",
+            "--count-only",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn run_repeat_shares_retry_budget() {
+    // Only 4 requests are answered; any retry past the budget would hit a
+    // closed port and fail with a connection error instead
+    let mock_url = start_mock_server_sequence(vec![
+        (500, r#"{"error": "boom"}"#),
+        (500, r#"{"error": "boom"}"#),
+        (500, r#"{"error": "boom"}"#),
+        (500, r#"{"error": "boom"}"#),
+    ]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--repeat",
+            "3",
+            "--verbose",
+            "--retries",
+            "2",
+            "--batch-retry-budget",
+            "1",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Run 1/3: failed: API error: 500"))
+        .stderr(predicate::str::contains("Run 2/3: failed: API error: 500"))
+        .stderr(predicate::str::contains("Run 3/3: failed: API error: 500"))
+        .stderr(predicate::str::contains("retry budget exhausted"));
+}
+
+#[test]
+fn run_repeat_streams_ndjson() {
+    let mock_url = start_mock_server_sequence(vec![
+        (200, r#"{"status": "ok"}"#),
+        (500, r#"{"error": "boom"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+
+    let output = snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--repeat",
+            "3",
+            "--output",
+            "ndjson",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .get_output()
+        .clone();
+
+    // Every line carries the fingerprint shown in the preview
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let fingerprint = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Params fingerprint: "))
+        .unwrap();
+    assert_eq!(fingerprint.len(), 8);
+
+    let mut lines: Vec<serde_json::Value> = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    for line in &mut lines {
+        let object = line.as_object_mut().unwrap();
+        let keys: Vec<&str> = object.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            ["error", "index", "params_fingerprint", "status", "webhook"]
+        );
+        assert_eq!(object.remove("params_fingerprint").unwrap(), fingerprint);
+    }
+    lines.sort_by_key(|line| line["index"].as_u64());
+    assert_eq!(
+        lines,
+        [
+            serde_json::json!({"index": 1, "webhook": "basic_test", "status": 200, "error": null}),
+            serde_json::json!({
+                "index": 2,
+                "webhook": "basic_test",
+                "status": 500,
+                "error": "API error: 500 on POST /launch/basic_test - {\"error\": \"boom\"}",
+            }),
+            serde_json::json!({"index": 3, "webhook": "basic_test", "status": 200, "error": null}),
+        ]
+    );
+}
+
+#[test]
+fn run_retries_extra_statuses() {
+    let mock_url = start_mock_server_sequence(vec![
+        (409, r#"{"error": "deploy in progress"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--retries",
+            "1",
+            "--retry-on",
+            "409,429",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("attempt 1 got 409 Conflict"));
+}
+
+#[test]
+fn run_rejects_out_of_range_retry_status() {
+    snouty()
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--retry-on",
+            "409,700",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("700"));
+}
+
+#[test]
+fn run_repeat_count_only_prints_summary() {
+    let mock_url = start_mock_server_sequence(vec![
+        (200, r#"{"status": "ok"}"#),
+        (500, r#"{"error": "boom"}"#),
+        (200, r#"{"status": "ok"}"#),
+    ]);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--repeat",
+            "3",
+            "--count-only",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stdout("launched=2 failed=1\n")
+        .stderr(predicate::str::contains("Run 1/3").not())
+        .stderr(predicate::str::contains("Launched 2 of 3 runs").not())
+        .stderr(predicate::str::contains("1 of 3 launches failed"));
+}
+
+#[test]
+fn run_explains_connection_refused() {
+    let unused_url = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    };
+
+    snouty_with_mock(&unused_url)
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "connection refused by 127.0.0.1; check that ANTITHESIS_BASE_URL points at a running server",
+        ));
+}
+
+#[test]
+fn run_fails_without_credentials() {
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env_remove("ANTITHESIS_TENANT")
+        .env_remove("ANTITHESIS_BASE_URL")
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing environment variable"));
+}
+
+#[test]
+fn run_reads_credentials_from_netrc() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+    let netrc = config_file("machine 127.0.0.1\n  login netrc-user\n  password netrc-pass\n");
+
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env("ANTITHESIS_TENANT", "testtenant")
+        .env("ANTITHESIS_BASE_URL", &mock_url)
+        .env("NETRC", netrc.path())
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("using credentials for 127.0.0.1"));
+
+    // No entry for this host, so the missing env vars are still an error
+    let netrc = config_file("machine example.com login a password b\n");
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env("ANTITHESIS_TENANT", "testtenant")
+        .env("ANTITHESIS_BASE_URL", &mock_url)
+        .env("NETRC", netrc.path())
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "missing environment variable: ANTITHESIS_USERNAME",
+        ));
+}
+
+#[test]
+fn run_fails_with_empty_tenant() {
+    snouty()
+        .env("ANTITHESIS_USERNAME", "testuser")
+        .env("ANTITHESIS_PASSWORD", "testpass")
+        .env("ANTITHESIS_TENANT", "  ")
+        .env_remove("ANTITHESIS_BASE_URL")
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "missing environment variable: ANTITHESIS_TENANT",
+        ));
+}
+
+#[test]
+fn only_validate_needs_no_credentials() {
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env_remove("ANTITHESIS_TENANT")
+        .env_remove("ANTITHESIS_BASE_URL")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--only-validate",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Params are valid"));
+
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env_remove("ANTITHESIS_TENANT")
+        .env_remove("ANTITHESIS_BASE_URL")
+        .args([
+            "debug",
+            "--only-validate",
+            "--antithesis.debugging.input_hash",
+            "abc",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("validation failed"));
+}
+
+#[test]
+fn only_validate_conflicts_with_body_file() {
+    // A body file skips schema validation, so there'd be nothing to validate
+    let body = config_file(r#"{"params": {"antithesis.duration": "soon"}}"#);
 
-    snouty_with_mock(&mock_url)
-        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["run", "-w", "basic_test", "--only-validate", "--body-file"])
+        .arg(body.path())
         .assert()
         .failure()
-        .stderr(predicate::str::contains("API error: 400"));
+        .stderr(predicate::str::contains("cannot be used with"))
+        .stderr(predicate::str::contains("Params are valid").not());
 }
 
 #[test]
-fn run_fails_without_credentials() {
-    snouty()
-        .env_remove("ANTITHESIS_USERNAME")
-        .env_remove("ANTITHESIS_PASSWORD")
-        .env_remove("ANTITHESIS_TENANT")
-        .env_remove("ANTITHESIS_BASE_URL")
-        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+fn only_validate_conflicts_with_report_url() {
+    // Fetching the report would need credentials and a request
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["debug", "--only-validate", "--report-url", "/report/abc"])
         .assert()
         .failure()
-        .stderr(predicate::str::contains("missing environment variable"));
+        .stderr(predicate::str::contains("cannot be used with"));
 }
 
 #[test]
@@ -481,3 +2921,528 @@ fn debug_merges_moment_with_cli_args() {
             r#""antithesis.report.recipients": "[REDACTED]""#,
         ));
 }
+
+#[test]
+fn run_local_sends_no_credentials() {
+    let (mock_url, requests) = start_recording_mock_server();
+
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .env_remove("ANTITHESIS_TENANT")
+        .env_remove("ANTITHESIS_BASE_URL")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--local",
+            &mock_url,
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+
+    let request = requests.recv().unwrap();
+    assert!(
+        request.starts_with("POST /launch/basic_test "),
+        "{}",
+        request
+    );
+    assert!(
+        !request.to_lowercase().contains("authorization:"),
+        "{}",
+        request
+    );
+}
+
+#[test]
+fn run_local_defaults_to_localhost() {
+    snouty()
+        .env_remove("ANTITHESIS_USERNAME")
+        .env_remove("ANTITHESIS_PASSWORD")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--local",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "'http://localhost:8080/launch/basic_test'",
+        ))
+        .stdout(predicate::str::contains("-u ").not());
+}
+
+#[test]
+fn run_email_buffer_changes_eta() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+    let config = config_file(r#"{ email_buffer_mins: 5 }"#);
+    let eta = |minutes| {
+        (chrono::Local::now() + chrono::Duration::minutes(minutes))
+            .format("%b %-d at %-I:%M %p")
+            .to_string()
+    };
+
+    // The flag takes priority over the config file
+    let before = eta(30 + 1440);
+    let output = snouty_with_mock(&mock_url)
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--email-buffer-mins",
+            "1440",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+    let after = eta(30 + 1440);
+    let stderr = String::from_utf8(output).unwrap();
+    assert!(
+        stderr.contains(&before) || stderr.contains(&after),
+        "{}",
+        stderr
+    );
+
+    snouty()
+        .args(["run", "-w", "basic_test", "--email-buffer-mins=-5"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--email-buffer-mins"));
+}
+
+#[test]
+fn run_reads_toml_from_stdin() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--stdin",
+            "--params-format",
+            "toml",
+            "--print-curl",
+        ])
+        .write_stdin("[antithesis]\nduration = \"30\"\n\n[antithesis.labels]\nteam = \"storage\"\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""antithesis.duration":"30""#))
+        .stdout(predicate::str::contains(
+            r#""antithesis.labels.team":"storage""#,
+        ));
+}
+
+#[test]
+fn run_require_field_passes_when_present() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--require-field",
+            "antithesis.report.recipients",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+            "--antithesis.report.recipients",
+            "team@example.com",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn run_require_field_names_missing_keys() {
+    let config = config_file(r#"{ required_fields: ["antithesis.source"] }"#);
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--require-field",
+            "antithesis.report.recipients",
+            "--print-curl",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "missing required params: antithesis.report.recipients, antithesis.source",
+        ));
+}
+
+#[test]
+fn run_param_prefix_namespaces_bare_keys() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--param-prefix",
+            "my.ns",
+            "--print-curl",
+            "--foo",
+            "bar",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""my.ns.foo":"bar""#))
+        .stdout(predicate::str::contains(r#""antithesis.duration":"30""#));
+}
+
+#[test]
+fn run_response_root_unwraps_envelope() {
+    let mock_url = start_mock_server(
+        r#"{"data": {"run_id": "abc", "run": {"attempt": 2}}, "meta": {"request_id": "r-1"}}"#,
+        200,
+    );
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--response-root",
+            "/data",
+            "--print-field",
+            "/run_id",
+            "--template",
+            "attempt={response.run.attempt}",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout("abc\nattempt=2\n");
+}
+
+#[test]
+fn debug_response_root_prints_nested_body() {
+    let mock_url = start_mock_server(r#"{"data": {"session": "s-1"}, "meta": {}}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "debug",
+            "--response-root",
+            "/data",
+            "--antithesis.debugging.session_id",
+            "f89d5c11f5e3bf5e4bb3641809800cee-44-22",
+            "--antithesis.debugging.input_hash",
+            "6057726200491963783",
+            "--antithesis.debugging.vtime",
+            "329.8037810830865",
+            "--antithesis.report.recipients",
+            "team@example.com",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""session": "s-1""#))
+        .stdout(predicate::str::contains("meta").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_exec_pipes_response_body() {
+    let body = r#"{"status": "ok", "run_id": "abc"}"#;
+    let mock_url = start_mock_server(body, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--exec",
+            "cat",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success()
+        .stdout(body);
+}
+
+#[cfg(unix)]
+#[test]
+fn run_exec_failure_sets_exit_code() {
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+
+    snouty_with_mock(&mock_url)
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--exec",
+            "cat >/dev/null; exit 3",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("--exec command"));
+}
+
+#[test]
+fn run_reads_params_from_prefixed_env_vars() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_PARAM_ANTITHESIS__DURATION", "30")
+        .env("SNOUTY_PARAM_ANTITHESIS__DESCRIPTION", "from env")
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--params-from-env",
+            "SNOUTY_PARAM_",
+            "--print-curl",
+            "--antithesis.description",
+            "from cli",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""antithesis.duration":"30""#))
+        .stdout(predicate::str::contains(
+            r#""antithesis.description":"from cli""#,
+        ));
+}
+
+#[test]
+fn run_protected_tenant_requires_confirmation() {
+    let config = config_file(r#"{ protected_tenants: ["testtenant"] }"#);
+
+    snouty_with_mock("http://127.0.0.1:1")
+        .env("SNOUTY_CONFIG", config.path())
+        .args(["run", "-w", "basic_test", "--antithesis.duration", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "tenant testtenant is protected; pass --confirm-prod",
+        ));
+
+    let mock_url = start_mock_server(r#"{"status": "ok"}"#, 200);
+    snouty_with_mock(&mock_url)
+        .env("SNOUTY_CONFIG", config.path())
+        .args([
+            "run",
+            "-w",
+            "basic_test",
+            "--confirm-prod",
+            "--antithesis.duration",
+            "30",
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn apply_launches_manifest() {
+    let (mock_url, requests) = start_recording_mock_server();
+    let manifest = config_file(
+        r#"
+webhook: basic_k8s_test
+recipients: team@example.com
+params:
+  antithesis.duration: 30
+  antithesis.images: app:latest
+"#,
+    );
+
+    snouty_with_mock(&mock_url)
+        .arg("apply")
+        .arg(manifest.path())
+        .args(["--antithesis.images", "app:v2"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#""antithesis.report.recipients": "[REDACTED]""#,
+        ));
+
+    let request = requests.recv().unwrap();
+    assert!(
+        request.starts_with("POST /launch/basic_k8s_test "),
+        "{}",
+        request
+    );
+    let body = request.split("\r\n\r\n").nth(1).unwrap();
+    let body: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "params": {
+                "antithesis.duration": "30",
+                "antithesis.images": "app:v2",
+                "antithesis.report.recipients": "team@example.com"
+            }
+        })
+    );
+}
+
+#[test]
+fn apply_params_are_not_command_line_args() {
+    let (mock_url, requests) = start_recording_mock_server();
+    let manifest = config_file(
+        r#"
+webhook: basic_test
+params:
+  antithesis.duration: 30
+  foo: -bar
+"#,
+    );
+
+    // --param-prefix and --strict-args only apply to the command line's params
+    snouty_with_mock(&mock_url)
+        .arg("apply")
+        .arg(manifest.path())
+        .args(["--param-prefix", "my.ns", "--strict-args", "--show-source"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            r#""foo": {
+    "source": "file",
+    "value": "-bar"
+  }"#,
+        ));
+
+    let request = requests.recv().unwrap();
+    let body = request.split("\r\n\r\n").nth(1).unwrap();
+    let body: serde_json::Value = serde_json::from_str(body).unwrap();
+    assert_eq!(
+        body,
+        serde_json::json!({
+            "params": { "antithesis.duration": "30", "foo": "-bar" }
+        })
+    );
+}
+
+#[test]
+fn apply_rejects_invalid_manifest() {
+    let manifest = config_file("webhook: basic_test\nrepeat: 3\n");
+
+    // No mock server: an invalid manifest must fail before launching
+    snouty_with_mock("http://127.0.0.1:1")
+        .arg("apply")
+        .arg(manifest.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "invalid manifest {}: unknown field `repeat`",
+            manifest.path().display()
+        )));
+}
+
+#[test]
+fn run_body_key_order() {
+    let body = |order: &str| {
+        let output = snouty_with_mock("http://127.0.0.1:1")
+            .args([
+                "run",
+                "-w",
+                "basic_test",
+                "--stdin",
+                "--print-curl",
+                "--body-key-order",
+                order,
+                "--antithesis.duration",
+                "30",
+            ])
+            .write_stdin(r#"{"zeta": "1", "alpha": "2"}"#)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert!(
+        body("sorted")
+            .contains(r#"{"params":{"alpha":"2","antithesis.duration":"30","zeta":"1"}}"#)
+    );
+    assert!(
+        body("insertion")
+            .contains(r#"{"params":{"zeta":"1","alpha":"2","antithesis.duration":"30"}}"#)
+    );
+}
+
+#[test]
+fn run_reports_json_error_location() {
+    snouty_with_mock("http://127.0.0.1:1")
+        .args(["run", "-w", "basic_test", "--stdin"])
+        .write_stdin(
+            "{\n  \"antithesis.duration\": \"30\",\n  \"antithesis.description\" \"x\"\n}\n",
+        )
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid JSON at line 3, column 28: expected colon",
+        ))
+        .stderr(predicate::str::contains(
+            r#"  3 |   "antithesis.description" "x""#,
+        ));
+}
+
+#[test]
+fn run_watch_file_relaunches_when_the_file_changes() {
+    let (mock_url, requests) = start_recording_mock_server();
+    let file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+    std::fs::write(file.path(), r#"{"antithesis.duration": "30"}"#).unwrap();
+
+    // The watcher never exits on its own, so run it directly rather than
+    // through assert_cmd, with a clean environment in place of with_test_env
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_snouty"))
+        .env_clear()
+        .env(
+            "NETRC",
+            concat!(env!("CARGO_TARGET_TMPDIR"), "/no-such-netrc"),
+        )
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env("ANTITHESIS_USERNAME", "testuser")
+        .env("ANTITHESIS_PASSWORD", "testpass")
+        .env("ANTITHESIS_TENANT", "testtenant")
+        .env("ANTITHESIS_BASE_URL", &mock_url)
+        .args(["run", "-w", "basic_test", "--watch-file"])
+        .arg(file.path())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let timeout = std::time::Duration::from_secs(30);
+    let first = requests.recv_timeout(timeout);
+    if first.is_ok() {
+        // Give the launch time to finish before changing the file
+        thread::sleep(std::time::Duration::from_millis(500));
+        std::fs::write(
+            file.path(),
+            r#"{"antithesis.duration": {"value": 45, "unit": "minutes"}}"#,
+        )
+        .unwrap();
+    }
+    let second = requests.recv_timeout(timeout);
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let first = first.unwrap();
+    assert!(first.starts_with("POST /launch/basic_test "), "{}", first);
+    assert!(first.contains(r#""antithesis.duration":"30""#), "{}", first);
+    // The relaunch re-read the file, and sent its structured duration as
+    // minutes
+    let second = second.unwrap();
+    assert!(second.starts_with("POST /launch/basic_test "), "{}", second);
+    assert!(
+        second.contains(r#""antithesis.duration":"45""#),
+        "{}",
+        second
+    );
+}